@@ -0,0 +1,114 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+//! Compares the single-allocation [`ErrorContext`] with the previous "fat" layout, which stored
+//! the message, the cause and the extensions inline.
+//!
+//! Run with `cargo bench --bench layout`.
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display},
+    hint::black_box,
+    io,
+    mem::size_of,
+    sync::Arc,
+    time::Instant,
+};
+
+use cloneable_errors::{ErrContext, ErrorContext, SharedString};
+
+const ITERATIONS: u32 = 1_000_000;
+
+/// A replica of the previous `ErrorContext` layout
+#[derive(Clone, Debug)]
+struct FatErrorContext {
+    context: SharedString,
+    cause: Option<Arc<dyn Error + Send + Sync + 'static>>,
+    #[allow(dead_code)] // only here to reproduce the size of the old layout
+    extensions: Option<Arc<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>>,
+}
+
+impl Display for FatErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.context)
+    }
+}
+
+impl Error for FatErrorContext {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.cause.as_deref().map(|cause| cause as &(dyn Error + 'static))
+    }
+}
+
+fn fat_context(cause: impl Error + Send + Sync + 'static, msg: &'static str) -> FatErrorContext {
+    FatErrorContext {
+        context: msg.into(),
+        cause: Some(Arc::new(cause)),
+        extensions: None,
+    }
+}
+
+fn measure(name: &str, mut f: impl FnMut()) {
+    // warm up
+    for _ in 0..ITERATIONS / 10 {
+        f();
+    }
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let nanos = start.elapsed().as_secs_f64() * 1e9 / f64::from(ITERATIONS);
+    println!("{name:<40} {nanos:>8.2} ns/iter");
+}
+
+#[inline(never)]
+fn fails_thin(fail: bool) -> Result<(), ErrorContext> {
+    if fail {
+        Err(ErrorContext::new("failed"))
+    } else {
+        Ok(())
+    }
+}
+
+#[inline(never)]
+fn fails_fat(fail: bool) -> Result<(), FatErrorContext> {
+    if fail {
+        Err(FatErrorContext {
+            context: "failed".into(),
+            cause: None,
+            extensions: None,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+fn main() {
+    println!("size_of::<Result<(), ErrorContext>>()     = {}", size_of::<Result<(), ErrorContext>>());
+    println!("size_of::<Result<(), FatErrorContext>>()  = {}", size_of::<Result<(), FatErrorContext>>());
+    println!();
+
+    let thin = io::Error::other("disk on fire").context("writing the file");
+    let fat = fat_context(io::Error::other("disk on fire"), "writing the file");
+    measure("clone (thin)", || drop(black_box(black_box(&thin).clone())));
+    measure("clone (fat)", || drop(black_box(black_box(&fat).clone())));
+
+    measure("wrap a cause (thin)", || {
+        drop(black_box(io::Error::other("disk on fire").context("writing the file")));
+    });
+    measure("wrap a cause (fat)", || {
+        drop(black_box(fat_context(io::Error::other("disk on fire"), "writing the file")));
+    });
+
+    measure("Ok through Result (thin)", || drop(black_box(fails_thin(black_box(false)))));
+    measure("Ok through Result (fat)", || drop(black_box(fails_fat(black_box(false)))));
+    measure("Err through Result (thin)", || drop(black_box(fails_thin(black_box(true)))));
+    measure("Err through Result (fat)", || drop(black_box(fails_fat(black_box(true)))));
+}
@@ -0,0 +1,230 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+//! Derive macros for the [`cloneable_errors`](https://docs.rs/cloneable_errors) crate
+//!
+//! Use them through the `derive` feature of `cloneable_errors` instead of depending on this crate
+//! directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr, Member, Path};
+
+/// The merge strategy requested using `#[extension(merge = "...")]`
+enum Merge {
+    Union,
+    Max,
+    Min,
+    Sum,
+    Custom(Path),
+}
+
+#[derive(Default)]
+struct Options {
+    display: bool,
+    debug: bool,
+    copy: bool,
+    tag: Option<LitStr>,
+    serialize: Option<Path>,
+    merge: Option<(Merge, LitStr)>,
+}
+
+impl Options {
+    fn parse(input: &DeriveInput) -> syn::Result<Options> {
+        let mut options = Options::default();
+        for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("extension")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("display") {
+                    options.display = true;
+                } else if meta.path.is_ident("debug") {
+                    options.debug = true;
+                } else if meta.path.is_ident("copy") {
+                    options.copy = true;
+                } else if meta.path.is_ident("tag") {
+                    options.tag = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("serialize") {
+                    options.serialize = Some(meta.path.clone());
+                } else if meta.path.is_ident("merge") {
+                    let lit: LitStr = meta.value()?.parse()?;
+                    let merge = match lit.value().as_str() {
+                        "union" => Merge::Union,
+                        "max" => Merge::Max,
+                        "min" => Merge::Min,
+                        "sum" => Merge::Sum,
+                        _ => Merge::Custom(lit.parse()?),
+                    };
+                    options.merge = Some((merge, lit));
+                } else {
+                    return Err(meta.error("unknown extension attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(options)
+    }
+}
+
+/// Returns the only field of a newtype-like struct, used by the `union` and `sum` strategies
+fn single_field(input: &DeriveInput, lit: &LitStr) -> syn::Result<Member> {
+    let error = || {
+        syn::Error::new_spanned(
+            lit,
+            "this merge strategy can only be used on structs with exactly one field",
+        )
+    };
+    let Data::Struct(data) = &input.data else {
+        return Err(error());
+    };
+    let mut fields = match &data.fields {
+        Fields::Named(fields) => fields.named.iter(),
+        Fields::Unnamed(fields) => fields.unnamed.iter(),
+        Fields::Unit => return Err(error()),
+    };
+    match (fields.next(), fields.next()) {
+        (Some(field), None) => Ok(field
+            .ident
+            .clone()
+            .map_or_else(|| Member::from(0), Member::Named)),
+        _ => Err(error()),
+    }
+}
+
+fn merge_impl(input: &DeriveInput, merge: &Merge, lit: &LitStr) -> syn::Result<TokenStream2> {
+    let body = match merge {
+        Merge::Union => {
+            let field = single_field(input, lit)?;
+            quote! {
+                Self {
+                    #field: self.#field.iter().chain(deeper.#field.iter()).cloned().collect(),
+                }
+            }
+        }
+        Merge::Sum => {
+            let field = single_field(input, lit)?;
+            quote! {
+                Self {
+                    #field: ::core::clone::Clone::clone(&self.#field)
+                        + ::core::clone::Clone::clone(&deeper.#field),
+                }
+            }
+        }
+        Merge::Max => quote! {
+            ::core::clone::Clone::clone(::core::cmp::max(self, deeper))
+        },
+        Merge::Min => quote! {
+            ::core::clone::Clone::clone(::core::cmp::min(self, deeper))
+        },
+        Merge::Custom(path) => quote! {
+            #path(self, deeper)
+        },
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::cloneable_errors::MergeExtension for #name #ty_generics #where_clause {
+            fn merge(&self, deeper: &Self) -> Self {
+                #body
+            }
+        }
+    })
+}
+
+fn derive(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let options = Options::parse(input)?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let display = options.display.then(|| {
+        quote! {
+            fn as_display(&self) -> ::core::option::Option<&dyn ::core::fmt::Display> {
+                ::core::option::Option::Some(self)
+            }
+        }
+    });
+    let debug = options.debug.then(|| {
+        quote! {
+            fn as_debug(&self) -> ::core::option::Option<&dyn ::core::fmt::Debug> {
+                ::core::option::Option::Some(self)
+            }
+        }
+    });
+    let copy = options.copy.then(|| {
+        quote! {
+            fn is_copyable(&self) -> bool {
+                true
+            }
+        }
+    });
+    let tag = options.tag.as_ref().map(|tag| {
+        quote! {
+            fn tag(&self) -> ::core::option::Option<&'static str> {
+                ::core::option::Option::Some(#tag)
+            }
+        }
+    });
+    let serialize = match (&options.serialize, &options.tag) {
+        (None, _) => TokenStream2::new(),
+        (Some(path), None) => {
+            return Err(syn::Error::new_spanned(path, "`serialize` requires a `tag` to be set"));
+        }
+        (Some(_), Some(tag)) => quote! {
+            impl #impl_generics ::cloneable_errors::SerializableExtension for #name #ty_generics #where_clause {
+                const TAG: &'static str = #tag;
+
+                fn encode(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    ::core::fmt::Display::fmt(self, f)
+                }
+
+                fn decode(data: &str) -> ::core::option::Option<Self> {
+                    ::core::result::Result::ok(<Self as ::core::str::FromStr>::from_str(data))
+                }
+            }
+        },
+    };
+    let merge = match &options.merge {
+        Some((merge, lit)) => merge_impl(input, merge, lit)?,
+        None => TokenStream2::new(),
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::cloneable_errors::Extension for #name #ty_generics #where_clause {
+            #display
+            #debug
+            #copy
+            #tag
+        }
+        #serialize
+        #merge
+    })
+}
+
+/// Derives the `Extension` trait
+///
+/// The generated implementation can be customized using the `#[extension(...)]` attribute:
+/// - `display`: render the extension in the `Debug` output of errors using its `Display` impl
+/// - `debug`: render the extension in the `Debug` output of errors using its `Debug` impl
+/// - `copy`: carry the extension over to copies made by `serializable_copy`
+/// - `tag = "name"`: set the stable name returned by `Extension::tag`. A tag alone does not make
+///   the extension serializable, see `serialize`
+/// - `serialize`: also implement `SerializableExtension` under the `tag`, encoding the extension
+///   with its `Display` impl and decoding it with its `FromStr` impl. The type still has to be
+///   registered with `register_extension::<T>()` on both the serializing and the deserializing
+///   side, as derive macros can't run code at startup
+/// - `merge = "strategy"`: also implement `MergeExtension`, where the strategy is one of:
+///   - `union`: chain the items of the only field of the struct, and collect them
+///     (a set union for set types, a concatenation for `Vec`)
+///   - `sum`: add the only fields of the struct together
+///   - `max`/`min`: keep the greater/lesser instance, according to its `Ord` impl
+///   - a path to a `fn(&Self, &Self) -> Self`, called with the shallower and deeper instance
+#[proc_macro_derive(Extension, attributes(extension))]
+pub fn derive_extension(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into_token_stream()
+        .into()
+}
@@ -0,0 +1,252 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use std::{
+    future::Future,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use crate::{ErrorClass, ErrorContext, IntoErrorIterator};
+
+/// The state of a [`CircuitBreaker`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls are allowed, failures are being counted
+    Closed,
+    /// Calls are rejected until the open duration passes
+    Open,
+    /// A single probe call is allowed to check whether the downstream has recovered
+    HalfOpen,
+}
+
+enum State {
+    Closed { failures: u32 },
+    Open { until: Instant, error: ErrorContext },
+    HalfOpen { probing: bool, error: ErrorContext },
+}
+
+/// A circuit breaker, which stops calling a failing downstream for a while
+///
+/// Failures are classified using a classifier function, which defaults to
+/// [`IntoErrorIterator::classify`]. Only [`ErrorClass::Retryable`] errors (like timeouts or
+/// refused connections) count towards tripping the breaker, while other errors (like validation
+/// errors) are treated as a sign of a responsive downstream.
+///
+/// Once the breaker is open, calls are rejected with a shared [`ErrorContext`] caused by the
+/// error that tripped the breaker. With the `extensions` feature enabled, errors returned while
+/// the breaker is open also have a [`crate::RetryAfter`] extension with the time remaining until
+/// the breaker becomes half-open.
+///
+/// After the open duration passes, a single probe call is allowed. If it succeeds, the breaker
+/// closes, otherwise it opens again.
+/// ```
+/// # use std::{io, time::Duration};
+/// # use cloneable_errors::{CircuitBreaker, CircuitState, ErrContext};
+/// let breaker = CircuitBreaker::new(2, Duration::from_secs(30));
+/// for _ in 0..2 {
+///     let _ = breaker.call(|| Err::<(), _>(io::Error::from(io::ErrorKind::TimedOut).context("calling the api")));
+/// }
+/// assert_eq!(breaker.state(), CircuitState::Open);
+///
+/// let error = breaker.call(|| Ok(())).unwrap_err();
+/// assert_eq!(format!("{error:?}"), "circuit breaker is open\n\nCaused by:\n    0: calling the api\n    1: timed out");
+/// ```
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_duration: Duration,
+    trip_unclassified: bool,
+    classifier: fn(&ErrorContext) -> Option<ErrorClass>,
+    clock: fn() -> Instant,
+    state: Mutex<State>,
+}
+
+impl CircuitBreaker {
+    /// Creates a new circuit breaker, which opens for `open_duration` after `failure_threshold`
+    /// consecutive retryable failures
+    #[must_use]
+    pub fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        CircuitBreaker {
+            failure_threshold: failure_threshold.max(1),
+            open_duration,
+            trip_unclassified: false,
+            classifier: |err| err.classify(),
+            clock: Instant::now,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    /// Sets whether errors that couldn't be classified should count as failures
+    ///
+    /// Disabled by default.
+    #[must_use]
+    pub fn trip_unclassified(mut self, trip: bool) -> Self {
+        self.trip_unclassified = trip;
+        self
+    }
+
+    /// Replaces the function used to classify errors
+    #[must_use]
+    pub fn with_classifier(mut self, classifier: fn(&ErrorContext) -> Option<ErrorClass>) -> Self {
+        self.classifier = classifier;
+        self
+    }
+
+    /// Replaces the source of the current time, which defaults to [`Instant::now`]
+    ///
+    /// Lets tests move the breaker between states without sleeping.
+    #[must_use]
+    pub fn with_clock(mut self, clock: fn() -> Instant) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().expect("Internal lock got poisoned")
+    }
+
+    /// Returns the current state of the breaker
+    #[must_use]
+    pub fn state(&self) -> CircuitState {
+        match &*self.lock() {
+            State::Closed { .. } => CircuitState::Closed,
+            State::Open { until, .. } if *until <= (self.clock)() => CircuitState::HalfOpen,
+            State::Open { .. } => CircuitState::Open,
+            State::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Returns the error that is being returned to rejected callers, if the breaker is not closed
+    #[must_use]
+    pub fn open_error(&self) -> Option<ErrorContext> {
+        match &*self.lock() {
+            State::Closed { .. } => None,
+            State::Open { error, .. } | State::HalfOpen { error, .. } => Some(error.clone()),
+        }
+    }
+
+    /// Closes the breaker and resets the failure count
+    pub fn reset(&self) {
+        *self.lock() = State::Closed { failures: 0 };
+    }
+
+    /// Runs the operation if the breaker allows it, recording its result
+    #[allow(clippy::missing_errors_doc)]
+    pub fn call<T, F>(&self, op: F) -> Result<T, ErrorContext>
+    where
+        F: FnOnce() -> Result<T, ErrorContext>,
+    {
+        let permit = self.acquire()?;
+        let result = op();
+        permit.record(&result);
+        result
+    }
+
+    /// Runs the future returned by the operation if the breaker allows it, recording its result
+    ///
+    /// If the returned future is dropped before completing, nothing is recorded.
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn call_async<T, F, Fut>(&self, op: F) -> Result<T, ErrorContext>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, ErrorContext>>,
+    {
+        let permit = self.acquire()?;
+        let result = op().await;
+        permit.record(&result);
+        result
+    }
+
+    fn acquire(&self) -> Result<Permit<'_>, ErrorContext> {
+        let mut state = self.lock();
+        let probe = match &mut *state {
+            State::Closed { .. } => false,
+            State::Open { until, error } => {
+                let now = (self.clock)();
+                if *until > now {
+                    #[cfg(feature = "extensions")]
+                    let error = error
+                        .clone()
+                        .with_extension(Arc::new(crate::RetryAfter(*until - now)));
+                    #[cfg(not(feature = "extensions"))]
+                    let error = error.clone();
+                    return Err(error);
+                }
+                *state = State::HalfOpen {
+                    probing: true,
+                    error: error.clone(),
+                };
+                true
+            }
+            State::HalfOpen { probing: true, error } => return Err(error.clone()),
+            State::HalfOpen { probing, .. } => {
+                *probing = true;
+                true
+            }
+        };
+        Ok(Permit {
+            breaker: self,
+            probe,
+            recorded: false,
+        })
+    }
+
+    fn trips(&self, err: &ErrorContext) -> bool {
+        (self.classifier)(err).map_or(self.trip_unclassified, ErrorClass::is_retryable)
+    }
+
+    fn open_with(&self, cause: &ErrorContext) -> State {
+        State::Open {
+            until: (self.clock)() + self.open_duration,
+            error: ErrorContext::from_parts("circuit breaker is open".into(), Some(Arc::new(cause.clone())), None),
+        }
+    }
+}
+
+/// Permission to make a call, releasing the probe slot if dropped without recording a result
+struct Permit<'a> {
+    breaker: &'a CircuitBreaker,
+    probe: bool,
+    recorded: bool,
+}
+
+impl Permit<'_> {
+    fn record<T>(mut self, result: &Result<T, ErrorContext>) {
+        self.recorded = true;
+        let failure = result.as_ref().err().filter(|err| self.breaker.trips(err));
+        let mut state = self.breaker.lock();
+        *state = match (failure, &*state) {
+            (None, State::Closed { .. }) => State::Closed { failures: 0 },
+            (None, State::HalfOpen { .. }) if self.probe => State::Closed { failures: 0 },
+            (Some(err), State::Closed { failures }) => {
+                let failures = failures + 1;
+                if failures >= self.breaker.failure_threshold {
+                    self.breaker.open_with(err)
+                } else {
+                    State::Closed { failures }
+                }
+            }
+            (Some(err), State::HalfOpen { .. }) if self.probe => self.breaker.open_with(err),
+            // the state was changed by a different call in the meantime
+            (_, State::Open { .. } | State::HalfOpen { .. }) => return,
+        };
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if self.recorded || !self.probe {
+            return;
+        }
+        // the probe was cancelled, let another call probe the downstream
+        if let Ok(mut state) = self.breaker.state.lock() {
+            if let State::HalfOpen { probing, .. } = &mut *state {
+                *probing = false;
+            }
+        }
+    }
+}
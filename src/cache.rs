@@ -0,0 +1,345 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    hash::Hash,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, MutexGuard,
+    },
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "extensions")]
+use crate::Extension;
+use crate::{ErrorContext, IntoErrorIterator};
+
+/// An extension that overrides the time an error will be stored in an [`ErrorCache`]
+///
+/// The nearest instance along the error chain caps the cache's failure TTL for that error: the
+/// error is stored for the shorter of the two durations. A zero duration prevents the error from
+/// being cached at all.
+#[cfg(feature = "extensions")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CacheTtl(pub Duration);
+
+#[cfg(feature = "extensions")]
+impl Extension for CacheTtl {}
+
+/// A snapshot of the statistics of an [`ErrorCache`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of lookups that returned a cached success
+    pub hits: u64,
+    /// Number of lookups that returned a cached error
+    pub error_hits: u64,
+    /// Number of lookups that found no valid entry
+    pub misses: u64,
+    /// Number of errors evicted before expiring, to stay under the size limit
+    pub evictions: u64,
+}
+
+struct Entry<T> {
+    value: Result<T, ErrorContext>,
+    expires: Instant,
+    /// estimated heap size of the error, 0 for successes
+    size: usize,
+}
+
+struct CacheState<K, T> {
+    entries: HashMap<K, Entry<T>>,
+    error_bytes: usize,
+}
+
+/// A thread-safe cache of `Result<T, ErrorContext>` values
+///
+/// Successes and errors are stored with separate TTLs, and the total estimated heap size of all
+/// stored errors (see [`IntoErrorIterator::estimated_size`](crate::IntoErrorIterator::estimated_size))
+/// can be limited. Lookups return cheap clones of the stored errors.
+///
+/// With the `extensions` feature enabled, the TTL of a single error can be shortened with the
+/// [`CacheTtl`] extension.
+///
+/// ```
+/// # use std::{sync::LazyLock, time::Duration};
+/// # use cloneable_errors::{bail, ErrorCache, ErrorContext};
+/// static CACHE: LazyLock<ErrorCache<u32, String>> =
+///     LazyLock::new(|| ErrorCache::new(Duration::from_secs(60), Duration::from_secs(5)));
+///
+/// fn lookup(id: u32) -> Result<String, ErrorContext> {
+///     CACHE.get_or_insert_with(id, || {
+///         if id == 0 {
+///             bail!("user 0 does not exist");
+///         }
+///         Ok(format!("user {id}"))
+///     })
+/// }
+///
+/// assert!(lookup(0).is_err());
+/// assert!(lookup(0).is_err());
+/// assert_eq!(lookup(1).unwrap(), "user 1");
+///
+/// let stats = CACHE.stats();
+/// assert_eq!(stats.error_hits, 1);
+/// assert_eq!(stats.misses, 2);
+/// ```
+pub struct ErrorCache<K, T> {
+    success_ttl: Duration,
+    failure_ttl: Duration,
+    max_error_bytes: Option<usize>,
+    clock: fn() -> Instant,
+    state: Mutex<CacheState<K, T>>,
+    hits: AtomicU64,
+    error_hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+impl<K, T> ErrorCache<K, T>
+where
+    K: Hash + Eq,
+    T: Clone,
+{
+    /// Creates a new cache, storing successful results for `success_ttl` and errors for
+    /// `failure_ttl`.
+    ///
+    /// A zero TTL disables caching of the given result variant.
+    #[must_use]
+    pub fn new(success_ttl: Duration, failure_ttl: Duration) -> Self {
+        ErrorCache {
+            success_ttl,
+            failure_ttl,
+            max_error_bytes: None,
+            clock: Instant::now,
+            state: Mutex::new(CacheState {
+                entries: HashMap::new(),
+                error_bytes: 0,
+            }),
+            hits: AtomicU64::new(0),
+            error_hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+        }
+    }
+
+    /// Limits the total estimated heap size of all stored errors
+    ///
+    /// When the limit is exceeded, expired errors are removed first, followed by the errors
+    /// closest to expiring.
+    #[must_use]
+    pub fn with_max_error_bytes(mut self, bytes: usize) -> Self {
+        self.max_error_bytes = Some(bytes);
+        self
+    }
+
+    /// Replaces the source of the current time, which defaults to [`Instant::now`]
+    ///
+    /// Mostly useful for testing expiry without waiting for real time to pass.
+    #[must_use]
+    pub fn with_clock(mut self, clock: fn() -> Instant) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheState<K, T>> {
+        self.state.lock().expect("Internal lock got poisoned")
+    }
+
+    /// Retrieves a clone of a stored result, if it exists and has not expired yet
+    pub fn get<Q>(&self, key: &Q) -> Option<Result<T, ErrorContext>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut state = self.lock();
+        let now = (self.clock)();
+
+        let result = match state.entries.get(key) {
+            Some(entry) if entry.expires > now => Some(entry.value.clone()),
+            Some(_) => {
+                if let Some(entry) = state.entries.remove(key) {
+                    state.error_bytes -= entry.size;
+                }
+                None
+            }
+            None => None,
+        };
+        drop(state);
+
+        let counter = match result {
+            Some(Ok(_)) => &self.hits,
+            Some(Err(_)) => &self.error_hits,
+            None => &self.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        result
+    }
+
+    /// Stores a result in the cache, replacing any previous entry for the same key
+    pub fn insert(&self, key: K, value: Result<T, ErrorContext>) {
+        let (ttl, size) = match &value {
+            Ok(_) => (self.success_ttl, 0),
+            Err(err) => (self.error_ttl(err), err.estimated_size()),
+        };
+        let mut state = self.lock();
+
+        if ttl.is_zero() || self.max_error_bytes.is_some_and(|max| size > max) {
+            if let Some(old) = state.entries.remove(&key) {
+                state.error_bytes -= old.size;
+            }
+            return;
+        }
+
+        let entry = Entry {
+            value,
+            expires: (self.clock)() + ttl,
+            size,
+        };
+        state.error_bytes += size;
+        if let Some(old) = state.entries.insert(key, entry) {
+            state.error_bytes -= old.size;
+        }
+
+        if let Some(max) = self.max_error_bytes {
+            if state.error_bytes > max {
+                self.shrink(&mut state, max);
+            }
+        }
+    }
+
+    /// Retrieves a stored result, or computes and stores a new one if none is found
+    ///
+    /// The function is called without holding any locks, which means that multiple threads
+    /// may compute the result for the same key at the same time.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn get_or_insert_with<F>(&self, key: K, f: F) -> Result<T, ErrorContext>
+    where
+        F: FnOnce() -> Result<T, ErrorContext>,
+    {
+        if let Some(result) = self.get(&key) {
+            return result;
+        }
+        let result = f();
+        self.insert(key, result.clone());
+        result
+    }
+
+    /// Removes an entry from the cache, returning the stored result if it has not expired yet
+    pub fn remove<Q>(&self, key: &Q) -> Option<Result<T, ErrorContext>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut state = self.lock();
+        let entry = state.entries.remove(key)?;
+        state.error_bytes -= entry.size;
+        (entry.expires > (self.clock)()).then_some(entry.value)
+    }
+
+    /// Removes all entries from the cache
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.entries.clear();
+        state.error_bytes = 0;
+    }
+
+    /// Removes all expired entries from the cache
+    ///
+    /// Expired entries are also removed when looked up, or when the size limit is reached.
+    pub fn purge_expired(&self) {
+        let now = (self.clock)();
+        Self::remove_where(&mut self.lock(), |entry| entry.expires <= now);
+    }
+
+    /// Returns the number of stored entries, including expired ones that were not removed yet
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Returns true if there are no entries stored in the cache
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lock().entries.is_empty()
+    }
+
+    /// Returns the total estimated heap size of all stored errors
+    #[must_use]
+    pub fn error_bytes(&self) -> usize {
+        self.lock().error_bytes
+    }
+
+    /// Returns a snapshot of the cache statistics
+    #[must_use]
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            error_hits: self.error_hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+        }
+    }
+
+    #[cfg(feature = "extensions")]
+    fn error_ttl(&self, err: &ErrorContext) -> Duration {
+        err.find_extension::<CacheTtl>().map_or(self.failure_ttl, |ttl| ttl.0.min(self.failure_ttl))
+    }
+
+    #[cfg(not(feature = "extensions"))]
+    fn error_ttl(&self, _err: &ErrorContext) -> Duration {
+        self.failure_ttl
+    }
+
+    /// Evicts errors until the total size is at most `max` bytes
+    fn shrink(&self, state: &mut CacheState<K, T>, max: usize) {
+        let now = (self.clock)();
+        Self::remove_where(state, |entry| entry.expires <= now);
+        if state.error_bytes <= max {
+            return;
+        }
+
+        // find the expiry time of the last error that has to be evicted
+        let mut errors: Vec<_> = state
+            .entries
+            .values()
+            .filter(|entry| entry.size > 0)
+            .map(|entry| (entry.expires, entry.size))
+            .collect();
+        errors.sort_unstable_by_key(|(expires, _)| *expires);
+        let mut remaining = state.error_bytes;
+        let mut cutoff = now;
+        for (expires, size) in errors {
+            if remaining <= max {
+                break;
+            }
+            remaining -= size;
+            cutoff = expires;
+        }
+
+        let evicted = Self::remove_where(state, |entry| entry.size > 0 && entry.expires <= cutoff);
+        self.evictions.fetch_add(evicted, Ordering::Relaxed);
+    }
+
+    /// Removes all entries matching the predicate, returning the number of removed entries
+    fn remove_where(state: &mut CacheState<K, T>, mut predicate: impl FnMut(&Entry<T>) -> bool) -> u64 {
+        let mut removed = 0;
+        let mut freed = 0;
+        state.entries.retain(|_, entry| {
+            if predicate(entry) {
+                removed += 1;
+                freed += entry.size;
+                false
+            } else {
+                true
+            }
+        });
+        state.error_bytes -= freed;
+        removed
+    }
+}
@@ -0,0 +1,76 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "extensions")]
+use crate::Extension;
+
+/// Describes whether an operation that failed with an error is worth retrying
+///
+/// Errors are classified by [`crate::ErrorIterator::classify`]. With the `extensions` feature
+/// enabled, an `ErrorClass` can be attached to an [`crate::ErrorContext`] as an extension to
+/// override the classification of the whole error stack below it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorClass {
+    /// A transient failure, like a timeout or an unavailable service
+    Retryable,
+    /// A failure that will happen again if the operation is repeated, like invalid input
+    Permanent,
+}
+
+#[cfg(feature = "extensions")]
+impl Extension for ErrorClass {}
+
+impl ErrorClass {
+    /// Classifies an [`io::ErrorKind`]
+    ///
+    /// Returns [`None`] for kinds which may or may not be transient, like [`io::ErrorKind::Other`].
+    /// Requires the `std` feature.
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn from_io_kind(kind: io::ErrorKind) -> Option<ErrorClass> {
+        use io::ErrorKind as K;
+        match kind {
+            K::Interrupted
+            | K::WouldBlock
+            | K::TimedOut
+            | K::ConnectionRefused
+            | K::ConnectionReset
+            | K::ConnectionAborted
+            | K::NotConnected
+            | K::BrokenPipe
+            | K::AddrInUse
+            | K::HostUnreachable
+            | K::NetworkUnreachable
+            | K::NetworkDown
+            | K::ResourceBusy
+            | K::UnexpectedEof
+            | K::OutOfMemory => Some(ErrorClass::Retryable),
+            K::NotFound
+            | K::PermissionDenied
+            | K::AlreadyExists
+            | K::InvalidInput
+            | K::InvalidData
+            | K::InvalidFilename
+            | K::IsADirectory
+            | K::NotADirectory
+            | K::DirectoryNotEmpty
+            | K::ReadOnlyFilesystem
+            | K::Unsupported
+            | K::AddrNotAvailable => Some(ErrorClass::Permanent),
+            _ => None,
+        }
+    }
+
+    /// Returns true if this is [`ErrorClass::Retryable`]
+    #[must_use]
+    pub fn is_retryable(self) -> bool {
+        self == ErrorClass::Retryable
+    }
+}
@@ -0,0 +1,155 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    error::Error,
+    fmt::{Debug, Display},
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
+
+use crate::{iterator::extract_message, IntoErrorIterator, SharedString};
+
+/// A wrapper that compares, hashes and orders the wrapped value by its contents
+///
+/// [`SharedString`] and [`crate::ErrorContext`] are compared using pointer equality, which makes
+/// them unsuitable as keys in maps and sets. Wrapping them in `ByContent` makes them compare
+/// by the contained strings instead:
+/// - `ByContent<SharedString>` compares the string values,
+/// - `ByContent<E>` for any error type compares the messages of every error in the
+///   [`Error::source`] chain, starting from the top.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// # use cloneable_errors::{anyhow, ByContent};
+/// let mut set = HashSet::new();
+/// assert!(set.insert(ByContent(anyhow!("error {}", 1))));
+/// assert!(!set.insert(ByContent(anyhow!("error {}", 1))));
+/// assert!(set.insert(ByContent(anyhow!("error {}", 2))));
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct ByContent<T>(pub T);
+
+impl<T> ByContent<T> {
+    /// Unwraps the inner value
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ByContent<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> From<T> for ByContent<T> {
+    fn from(value: T) -> Self {
+        ByContent(value)
+    }
+}
+
+impl<T: Debug> Debug for ByContent<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: Display> Display for ByContent<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+// SharedString
+
+impl PartialEq for ByContent<SharedString> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Eq for ByContent<SharedString> {}
+
+impl PartialOrd for ByContent<SharedString> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ByContent<SharedString> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.as_str().cmp(other.0.as_str())
+    }
+}
+
+impl Hash for ByContent<SharedString> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.as_str().hash(state);
+    }
+}
+
+/// Allows looking up `ByContent<SharedString>` keys in maps and sets using a `&str`.
+impl Borrow<str> for ByContent<SharedString> {
+    fn borrow(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+// errors
+
+impl<T: Error + 'static> PartialEq for ByContent<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Error + 'static> Eq for ByContent<T> {}
+
+impl<T: Error + 'static> PartialOrd for ByContent<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Errors are ordered lexicographically by their messages, starting from the top of the chain.
+/// A chain that is a prefix of another chain is ordered before it.
+impl<T: Error + 'static> Ord for ByContent<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let mut ours = self.0.error_chain();
+        let mut theirs = other.0.error_chain();
+        loop {
+            match (ours.next(), theirs.next()) {
+                (None, None) => return Ordering::Equal,
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+                (Some(a), Some(b)) => {
+                    match extract_message(a).as_str().cmp(extract_message(b).as_str()) {
+                        Ordering::Equal => {}
+                        ord => return ord,
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T: Error + 'static> Hash for ByContent<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut len = 0usize;
+        for err in self.0.error_chain() {
+            extract_message(err).as_str().hash(state);
+            len += 1;
+        }
+        len.hash(state);
+    }
+}
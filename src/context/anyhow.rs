@@ -5,9 +5,15 @@
 * Copyright (C) 2024-2025 mini_bomba
 */
 
-use std::sync::Arc;
+use std::{
+    backtrace::Backtrace,
+    error::Error,
+    fmt::{Debug, Display},
+    ops::Deref,
+    sync::Arc,
+};
 
-use crate::{ErrorContext, SharedString};
+use crate::{ErrContext, ErrorContext, IntoErrorIterator, SharedString};
 
 impl From<anyhow::Error> for ErrorContext {
     /// <div class="warning">
@@ -15,28 +21,165 @@ impl From<anyhow::Error> for ErrorContext {
     /// NOTE: Converting [`anyhow::Error`] into [`ErrorContext`] causes the anyhow error stack to
     ///       be flattened into a stack of string errors! Extracting the error type-specific data
     ///       that is not exposed in the [`std::fmt::Display`] impl will not be possible!
+    ///       Use [`AnyhowErrContext::context`] or [`AnyhowCause`] to keep the original error.
     ///
     /// </div>
+    ///
+    /// Anyhow errors created from an [`ErrorContext`] (see [`ErrorContext::into_anyhow`]) are
+    /// converted back without flattening.
     fn from(value: anyhow::Error) -> Self {
+        let value = match value.downcast::<ErrorContext>() {
+            Ok(error) => return error,
+            Err(value) => value,
+        };
         let flattened = crate::SerializableError::from_anyhow(&value);
-        ErrorContext {
-            context: flattened.context,
-            cause: flattened
+        ErrorContext::from_parts(
+            flattened.context,
+            flattened
                 .cause
-                .map(|arc| arc as Arc<(dyn std::error::Error + Send + Sync + 'static)>),
-            #[cfg(feature = "extensions")]
-            extensions: None,
-        }
+                .map(|arc| arc as Arc<dyn Error + Send + Sync + 'static>),
+            None,
+        )
+    }
+}
+
+/// A cloneable wrapper around an [`anyhow::Error`], which can be used as the cause of an
+/// [`ErrorContext`]
+///
+/// Unlike converting the anyhow error into an [`ErrorContext`], this keeps the original error
+/// stack: [`crate::ErrorIterator`] sees through this wrapper, so the typed errors inside can still
+/// be found with `downcast_ref`, and the backtrace captured by anyhow stays available.
+/// ```
+/// # use std::io;
+/// # use cloneable_errors::{AnyhowResContext, IntoErrorIterator};
+/// let result: anyhow::Result<()> = Err(anyhow::Error::new(io::Error::other("disk on fire")));
+/// let error = result.context("handling the request").unwrap_err();
+///
+/// assert!(error.error_chain().any(|err| err.is::<io::Error>()));
+/// assert!(error.anyhow_backtrace().is_some());
+/// ```
+///
+/// Note that [`anyhow::Error::context`] shadows [`AnyhowErrContext::context`] when called as a
+/// method on an [`anyhow::Error`].
+#[derive(Clone)]
+pub struct AnyhowCause(Arc<anyhow::Error>);
+
+impl AnyhowCause {
+    /// Returns the backtrace captured by anyhow
+    ///
+    /// See [`anyhow::Error::backtrace`].
+    pub fn backtrace(&self) -> &Backtrace {
+        self.0.backtrace()
+    }
+
+    /// Returns the wrapped anyhow error
+    #[must_use]
+    pub fn into_inner(self) -> Arc<anyhow::Error> {
+        self.0
+    }
+}
+
+impl From<anyhow::Error> for AnyhowCause {
+    fn from(value: anyhow::Error) -> Self {
+        AnyhowCause(Arc::new(value))
+    }
+}
+
+impl Deref for AnyhowCause {
+    type Target = anyhow::Error;
+
+    fn deref(&self) -> &anyhow::Error {
+        &self.0
+    }
+}
+
+impl Display for AnyhowCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&*self.0, f)
+    }
+}
+
+impl Debug for AnyhowCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&*self.0, f)
+    }
+}
+
+impl Error for AnyhowCause {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.source()
+    }
+}
+
+/// An unwrapper for [`AnyhowCause`], returning the outermost error of the anyhow stack
+pub(crate) fn unwrap_anyhow<'a>(err: &'a (dyn Error + 'static)) -> Option<&'a (dyn Error + 'static)> {
+    err.downcast_ref::<AnyhowCause>()
+        .map(|cause| &**cause.0 as &(dyn Error + 'static))
+}
+
+impl ErrorContext {
+    /// Converts this error into an [`anyhow::Error`]
+    ///
+    /// The error stack is not flattened: every layer stays reachable through
+    /// [`anyhow::Error::chain`], and [`anyhow::Error::downcast_ref`] gives back this
+    /// `ErrorContext`, with its extensions attached. Converting the anyhow error back into an
+    /// [`ErrorContext`] returns the original error.
+    ///
+    /// The message and the chain of the anyhow error are the ones of this error. Extensions are not
+    /// rendered by anyhow, and are only reachable by downcasting.
+    /// ```
+    /// # use std::io;
+    /// # use cloneable_errors::{ErrContext, ErrorContext};
+    /// let error = io::Error::other("disk on fire").context("saving the file");
+    /// let anyhow = error.clone().into_anyhow();
+    ///
+    /// assert_eq!(format!("{anyhow:#}"), "saving the file: disk on fire");
+    /// assert!(anyhow.chain().any(|err| err.is::<io::Error>()));
+    /// assert_eq!(anyhow.downcast_ref::<ErrorContext>(), Some(&error));
+    /// assert_eq!(ErrorContext::from(anyhow), error);
+    /// ```
+    #[must_use]
+    pub fn into_anyhow(self) -> anyhow::Error {
+        anyhow::Error::new(self)
+    }
+
+    /// Returns the outermost [`ErrorContext`] in an anyhow error stack, if any
+    ///
+    /// Unlike [`anyhow::Error::downcast_ref`], this also finds errors wrapped by
+    /// [`anyhow::Error::context`].
+    #[must_use]
+    pub fn find_in_anyhow(error: &anyhow::Error) -> Option<&ErrorContext> {
+        error.chain().find_map(|err| err.downcast_ref::<ErrorContext>())
+    }
+
+    /// Returns the backtrace captured by the outermost anyhow error in the stack, if any
+    ///
+    /// Only anyhow errors kept using [`AnyhowCause`] are searched, flattened ones don't keep
+    /// their backtraces.
+    #[must_use]
+    pub fn anyhow_backtrace(&self) -> Option<&Backtrace> {
+        self.error_chain()
+            .filter_map(|err| err.downcast_ref::<ErrorContext>())
+            .find_map(|err| err.cause()?.downcast_ref::<AnyhowCause>())
+            .map(AnyhowCause::backtrace)
     }
 }
 
 /// A helper trait for converting an anyhow error stack into an [`ErrorContext`] stack
 pub trait AnyhowErrContext {
+    /// Wrap this anyhow error into a new [`ErrorContext`] error, annotated with the specified
+    /// context message
+    ///
+    /// The anyhow error is kept as-is in an [`AnyhowCause`].
+    fn context<M>(self, msg: M) -> ErrorContext
+    where
+        M: Into<SharedString>;
+
     /// Convert this anyhow error into a new [`ErrorContext`] error, annotated with the specified context message
     ///
     /// Note: This function will flatten the entire error stack into a [`crate::SerializableError`], any data not
     ///       exposed in the Display implementations of errors will be lost!
-    fn context<M>(self, msg: M) -> ErrorContext
+    fn flattened_context<M>(self, msg: M) -> ErrorContext
     where
         M: Into<SharedString>;
 }
@@ -46,12 +189,14 @@ impl AnyhowErrContext for anyhow::Error {
     where
         M: Into<SharedString>,
     {
-        ErrorContext {
-            context: msg.into(),
-            cause: Some(Arc::new(ErrorContext::from(self))),
-            #[cfg(feature = "extensions")]
-            extensions: None,
-        }
+        ErrContext::context(AnyhowCause::from(self), msg)
+    }
+
+    fn flattened_context<M>(self, msg: M) -> ErrorContext
+    where
+        M: Into<SharedString>,
+    {
+        ErrorContext::from_parts(msg.into(), Some(Arc::new(ErrorContext::from(self))), None)
     }
 }
 
@@ -60,9 +205,7 @@ impl AnyhowErrContext for anyhow::Error {
 pub trait AnyhowResContext<T, E> {
     /// Map the anyhow error into a new [`ErrorContext`] error, annotated with the specified context message
     ///
-    /// Note: This function will flatten the entire error stack into a [`crate::SerializableError`], any data not
-    ///       exposed in the Display implementations of errors will be lost!
-    ///       (this only applies if the result error is an anyhow error)
+    /// See [`AnyhowErrContext::context`].
     fn context<M>(self, msg: M) -> Result<T, ErrorContext>
     where
         M: Into<SharedString>;
@@ -70,13 +213,20 @@ pub trait AnyhowResContext<T, E> {
     /// Map the anyhow error into a new [`ErrorContext`] error, annotated with a dynamically
     /// computed context message
     ///
-    /// Note: This function will flatten the entire error stack into a [`crate::SerializableError`], any data not
-    ///       exposed in the Display implementations of errors will be lost!
-    ///       (this only applies if the result error is an anyhow error)
+    /// See [`AnyhowErrContext::context`].
     fn with_context<M, F>(self, f: F) -> Result<T, ErrorContext>
     where
         M: Into<SharedString>,
         F: FnOnce() -> M;
+
+    /// Map the anyhow error into a new [`ErrorContext`] error, annotated with the specified context message
+    ///
+    /// Note: This function will flatten the entire error stack into a [`crate::SerializableError`], any data not
+    ///       exposed in the Display implementations of errors will be lost!
+    ///       (this only applies if the result error is an anyhow error)
+    fn flattened_context<M>(self, msg: M) -> Result<T, ErrorContext>
+    where
+        M: Into<SharedString>;
 }
 
 impl<T, E> AnyhowResContext<T, E> for Result<T, E>
@@ -97,4 +247,11 @@ where
     {
         self.map_err(|e| e.context(f()))
     }
+
+    fn flattened_context<M>(self, msg: M) -> Result<T, ErrorContext>
+    where
+        M: Into<SharedString>,
+    {
+        self.map_err(|e| e.flattened_context(msg))
+    }
 }
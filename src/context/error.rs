@@ -5,9 +5,10 @@
 * Copyright (C) 2024-2025 mini_bomba
 */
 
-use std::{error::Error, sync::Arc};
+use alloc::sync::Arc;
+use core::error::Error;
 
-use crate::{ErrorContext, SharedString};
+use crate::{unwrap::unwrapped_type_name, ErrorContext, SharedString};
 
 /// A helper trait for annotating any Error with an [`ErrorContext`]
 pub trait ErrContext {
@@ -15,6 +16,17 @@ pub trait ErrContext {
     fn context<M>(self, msg: M) -> ErrorContext
     where
         M: Into<SharedString>;
+
+    /// Wrap this error into a new [`ErrorContext`] error, annotated with the specified context
+    /// and extension
+    #[cfg(feature = "extensions")]
+    fn context_with_ext<M>(self, msg: M, ext: Arc<dyn crate::Extension>) -> ErrorContext
+    where
+        M: Into<SharedString>,
+        Self: Sized,
+    {
+        self.context(msg).with_extension(ext)
+    }
 }
 
 impl<T> ErrContext for T
@@ -25,11 +37,7 @@ where
     where
         M: Into<SharedString>,
     {
-        ErrorContext {
-            context: msg.into(),
-            cause: Some(Arc::new(self)),
-            #[cfg(feature = "extensions")]
-            extensions: None,
-        }
+        let type_name = unwrapped_type_name(&self, core::any::type_name::<T>());
+        ErrorContext::from_parts(msg.into(), Some(Arc::new(self)), type_name)
     }
 }
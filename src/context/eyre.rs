@@ -0,0 +1,73 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use std::{error::Error, sync::Arc};
+
+use crate::ErrorContext;
+
+impl From<eyre::Report> for ErrorContext {
+    /// <div class="warning">
+    ///
+    /// NOTE: Converting [`eyre::Report`] into [`ErrorContext`] causes the eyre error stack to be
+    ///       flattened into a stack of string errors! Extracting the error type-specific data
+    ///       that is not exposed in the [`std::fmt::Display`] impl will not be possible!
+    ///
+    /// </div>
+    ///
+    /// Reports created from an [`ErrorContext`] (see [`ErrorContext::into_eyre`]) are converted
+    /// back without flattening.
+    fn from(value: eyre::Report) -> Self {
+        let value = match value.downcast::<ErrorContext>() {
+            Ok(error) => return error,
+            Err(value) => value,
+        };
+        let flattened = crate::SerializableError::from_eyre(&value);
+        ErrorContext::from_parts(
+            flattened.context,
+            flattened
+                .cause
+                .map(|arc| arc as Arc<dyn Error + Send + Sync + 'static>),
+            None,
+        )
+    }
+}
+
+impl ErrorContext {
+    /// Converts this error into an [`eyre::Report`]
+    ///
+    /// The error stack is not flattened: every layer stays reachable through
+    /// [`eyre::Report::chain`], and [`eyre::Report::downcast_ref`] gives back this
+    /// `ErrorContext`, with its extensions attached. Converting the report back into an
+    /// [`ErrorContext`] returns the original error.
+    ///
+    /// The message and the chain of the report are the ones of this error. Extensions are not
+    /// rendered by eyre, and are only reachable by downcasting.
+    /// ```
+    /// # use std::io;
+    /// # use cloneable_errors::{ErrContext, ErrorContext};
+    /// let error = io::Error::other("disk on fire").context("saving the file");
+    /// let report = error.clone().into_eyre();
+    ///
+    /// assert_eq!(format!("{report:#}"), "saving the file: disk on fire");
+    /// assert!(report.chain().any(|err| err.is::<io::Error>()));
+    /// assert_eq!(report.downcast_ref::<ErrorContext>(), Some(&error));
+    /// assert_eq!(ErrorContext::from(report), error);
+    /// ```
+    #[must_use = "the error is moved into the returned report"]
+    pub fn into_eyre(self) -> eyre::Report {
+        eyre::Report::new(self)
+    }
+
+    /// Returns the outermost [`ErrorContext`] in an eyre error stack, if any
+    ///
+    /// Unlike [`eyre::Report::downcast_ref`], this also finds errors wrapped by
+    /// [`eyre::Report::wrap_err`].
+    #[must_use]
+    pub fn find_in_eyre(report: &eyre::Report) -> Option<&ErrorContext> {
+        report.chain().find_map(|err| err.downcast_ref::<ErrorContext>())
+    }
+}
@@ -8,15 +8,20 @@
 #[cfg(feature = "anyhow")]
 mod anyhow;
 mod error;
+#[cfg(feature = "eyre")]
+mod eyre;
 mod result;
 #[cfg(feature = "extensions")]
 mod result_extensions;
 mod r#struct;
 
 #[cfg(feature = "anyhow")]
-pub use anyhow::{AnyhowErrContext, AnyhowResContext};
+pub(crate) use anyhow::unwrap_anyhow;
+#[cfg(feature = "anyhow")]
+pub use anyhow::{AnyhowCause, AnyhowErrContext, AnyhowResContext};
 pub use error::ErrContext;
+pub(crate) use r#struct::write_chain;
 pub use r#struct::ErrorContext;
 pub use result::ResContext;
 #[cfg(feature = "extensions")]
-pub use result_extensions::ResExtensions;
+pub use result_extensions::{ResContextExtensions, ResExtensions};
@@ -5,7 +5,7 @@
 * Copyright (C) 2024-2025 mini_bomba
 */
 
-use std::convert::Infallible;
+use core::convert::Infallible;
 
 use crate::{ErrContext, ErrorContext, SharedString};
 
@@ -51,12 +51,7 @@ impl<T> ResContext<T, Infallible> for Option<T> {
     where
         M: Into<SharedString>,
     {
-        self.ok_or_else(|| ErrorContext {
-            context: msg.into(),
-            cause: None,
-            #[cfg(feature = "extensions")]
-            extensions: None,
-        })
+        self.ok_or_else(|| ErrorContext::new(msg))
     }
 
     fn with_context<M, F>(self, f: F) -> Result<T, ErrorContext>
@@ -64,11 +59,6 @@ impl<T> ResContext<T, Infallible> for Option<T> {
         M: Into<SharedString>,
         F: FnOnce() -> M,
     {
-        self.ok_or_else(|| ErrorContext {
-            context: f().into(),
-            cause: None,
-            #[cfg(feature = "extensions")]
-            extensions: None,
-        })
+        self.ok_or_else(|| ErrorContext::new(f()))
     }
 }
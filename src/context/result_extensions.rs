@@ -5,9 +5,10 @@
 * Copyright (C) 2025 mini_bomba
 */
 
-use std::sync::Arc;
+use alloc::sync::Arc;
+use core::convert::Infallible;
 
-use crate::{ErrorContext, Extension};
+use crate::{ErrContext, ErrorContext, Extension, SharedString};
 
 #[allow(clippy::missing_errors_doc)]
 /// A helper trait for extending error variants of `Result<T, ErrorContext>`
@@ -37,3 +38,74 @@ impl<T> ResExtensions<T> for Result<T, ErrorContext> {
         self.map_err(ErrorContext::without_extension::<E>)
     }
 }
+
+#[allow(clippy::missing_errors_doc)]
+/// A helper trait for annotating result errors and empty options with a context message and an
+/// extension at once
+pub trait ResContextExtensions<T, E> {
+    /// Map the error into a new [`ErrorContext`] error, annotated with a specified context message
+    /// and extension
+    /// ```
+    /// # use std::{io, sync::Arc};
+    /// # use cloneable_errors::{ErrorClass, IntoErrorIterator, ResContextExtensions};
+    /// let result: Result<(), _> = Err(io::Error::other("connection lost"));
+    /// let error = result.context_with_ext("syncing", Arc::new(ErrorClass::Retryable)).unwrap_err();
+    /// assert_eq!(format!("{error}"), "syncing");
+    /// assert_eq!(error.get_extension::<ErrorClass>().as_deref(), Some(&ErrorClass::Retryable));
+    /// ```
+    fn context_with_ext<M>(self, msg: M, ext: Arc<dyn Extension>) -> Result<T, ErrorContext>
+    where
+        M: Into<SharedString>;
+
+    /// Map the error into a new [`ErrorContext`] error, annotated with a dynamically computed
+    /// context message and extension
+    ///
+    /// The function is only called if the result is an error variant
+    fn with_context_ext<M, F>(self, f: F) -> Result<T, ErrorContext>
+    where
+        M: Into<SharedString>,
+        F: FnOnce() -> (M, Arc<dyn Extension>);
+}
+
+impl<T, E> ResContextExtensions<T, E> for Result<T, E>
+where
+    E: ErrContext,
+{
+    fn context_with_ext<M>(self, msg: M, ext: Arc<dyn Extension>) -> Result<T, ErrorContext>
+    where
+        M: Into<SharedString>,
+    {
+        self.map_err(|e| e.context_with_ext(msg, ext))
+    }
+
+    fn with_context_ext<M, F>(self, f: F) -> Result<T, ErrorContext>
+    where
+        M: Into<SharedString>,
+        F: FnOnce() -> (M, Arc<dyn Extension>),
+    {
+        self.map_err(|e| {
+            let (msg, ext) = f();
+            e.context_with_ext(msg, ext)
+        })
+    }
+}
+
+impl<T> ResContextExtensions<T, Infallible> for Option<T> {
+    fn context_with_ext<M>(self, msg: M, ext: Arc<dyn Extension>) -> Result<T, ErrorContext>
+    where
+        M: Into<SharedString>,
+    {
+        self.ok_or_else(|| ErrorContext::new(msg).with_extension(ext))
+    }
+
+    fn with_context_ext<M, F>(self, f: F) -> Result<T, ErrorContext>
+    where
+        M: Into<SharedString>,
+        F: FnOnce() -> (M, Arc<dyn Extension>),
+    {
+        self.ok_or_else(|| {
+            let (msg, ext) = f();
+            ErrorContext::new(msg).with_extension(ext)
+        })
+    }
+}
@@ -5,50 +5,102 @@
 * Copyright (C) 2024-2025 mini_bomba
 */
 
-use std::{
+use alloc::sync::Arc;
+use core::{
     error::Error,
     fmt::{Debug, Display},
-    sync::Arc,
+    ptr,
 };
 #[cfg(feature = "extensions")]
-use std:: {
-    any::TypeId,
-    collections::HashMap,
-    ops::Deref,
-};
+use core::any::{Any, TypeId};
 
 #[cfg(feature = "extensions")]
-use crate::extensions::{Extension, ExtensionMap, MaskExtension};
+use crate::extensions::{
+    insert_extension, remove_extension, visible_extensions, write_extensions, Extension,
+};
+#[cfg(feature = "extensions")]
+use crate::ExtensionMap;
 use crate::util::option_ptr_eq;
-use crate::IntoErrorIterator;
-use crate::SharedString;
+use crate::{iterator::ARC_OVERHEAD, ErrorIterator, IntoErrorIterator, SharedString};
 
 #[derive(Clone)]
-#[non_exhaustive]
 /// An annotated error stack
+///
+/// An `ErrorContext` is a single pointer to a shared allocation holding the message, the cause
+/// and the extensions of the error. Cloning it only increments one reference count, and
+/// `Result<(), ErrorContext>` is the size of a pointer.
 pub struct ErrorContext {
-    pub context: SharedString,
-    pub cause: Option<Arc<dyn Error + Send + Sync + 'static>>,
+    inner: Arc<Inner>,
+}
+
+#[derive(Clone)]
+struct Inner {
+    context: SharedString,
+    cause: Option<Arc<dyn Error + Send + Sync + 'static>>,
+    cause_type_name: Option<&'static str>,
     #[cfg(feature = "extensions")]
-    pub extensions: Option<ExtensionMap>,
+    extensions: Option<ExtensionMap>,
 }
 
 impl ErrorContext {
+    /// The heap memory used by a single `ErrorContext`, excluding its message and extensions
+    pub(crate) const ALLOCATION_SIZE: usize = ARC_OVERHEAD + size_of::<Inner>();
+
+    pub(crate) fn from_parts(
+        context: SharedString,
+        cause: Option<Arc<dyn Error + Send + Sync + 'static>>,
+        cause_type_name: Option<&'static str>,
+    ) -> ErrorContext {
+        ErrorContext {
+            inner: Arc::new(Inner {
+                context,
+                cause,
+                cause_type_name,
+                #[cfg(feature = "extensions")]
+                extensions: None,
+            }),
+        }
+    }
+
+    /// Returns the message of this error
+    #[must_use]
+    pub fn message(&self) -> &SharedString {
+        &self.inner.context
+    }
+
+    /// Returns the error that caused this error, if any
+    #[must_use]
+    pub fn cause(&self) -> Option<&Arc<dyn Error + Send + Sync + 'static>> {
+        self.inner.cause.as_ref()
+    }
+
+    /// Returns the [`std::any::type_name`] of the cause, if known
+    ///
+    /// Captured by [`crate::ErrContext::context`], shown by [`crate::ChainLink::type_name`].
+    /// Not recorded when the cause is a wrapper that [`crate::ErrorIterator`] sees through.
+    #[must_use]
+    pub fn cause_type_name(&self) -> Option<&'static str> {
+        self.inner.cause_type_name
+    }
+
+    /// Returns the extensions attached directly to this error, if any
+    #[cfg(feature = "extensions")]
+    #[must_use]
+    pub fn extension_map(&self) -> Option<&ExtensionMap> {
+        self.inner.extensions.as_ref()
+    }
+
     /// Creates a new `ErrorContext` with the given initial error message.
     ///
     /// The error message should either be a `&'static str`, or an `Arc<str>`.
-    /// `String` is also accepted, but will be converted to an `Arc<str>`.
+    /// `String` is also accepted, and will be stored inline if it's short enough, or converted to
+    /// an `Arc<str>` otherwise.
     #[must_use]
     pub fn new<T>(msg: T) -> ErrorContext
     where
         T: Into<SharedString>,
     {
-        ErrorContext {
-            context: msg.into(),
-            cause: None,
-            #[cfg(feature = "extensions")]
-            extensions: None,
-        }
+        ErrorContext::from_parts(msg.into(), None, None)
     }
 }
 
@@ -71,17 +123,65 @@ impl ErrorContext {
     /// Only one instance of any extension can be attached to an error at a time - adding another
     /// instance of an existing extension type overwrites the previous entry.
     pub fn add_extension(&mut self, ext: Arc<dyn Extension>) {
-        match self.extensions {
-            None => {
-                let mut extensions = HashMap::with_capacity(1);
-                extensions.insert(ext.deref().type_id(), ext);
-                self.extensions = Some(Arc::from(extensions));
-            }
-            Some(ref mut extensions) => {
-                let extensions = Arc::make_mut(extensions);
-                extensions.insert(ext.deref().type_id(), ext);
-            }
+        insert_extension(&mut Arc::make_mut(&mut self.inner).extensions, ext);
+    }
+
+    /// Returns a mutable reference to the extension of a given type attached directly to this
+    /// error, if it exists.
+    ///
+    /// Extensions attached to the causes of this error are not considered. The extension (and the
+    /// map holding it) is cloned first if it's shared with other errors, so clones of this error
+    /// are not affected.
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use cloneable_errors::{anyhow, Extension, IntoErrorIterator};
+    /// #[derive(Clone)]
+    /// struct Attempts(u32);
+    /// impl Extension for Attempts {}
+    ///
+    /// let original = anyhow!("request failed", extend: Arc::new(Attempts(1)));
+    /// let mut error = original.clone();
+    /// error.extension_mut::<Attempts>().unwrap().0 += 1;
+    ///
+    /// assert_eq!(error.find_extension::<Attempts>().unwrap().0, 2);
+    /// assert_eq!(original.find_extension::<Attempts>().unwrap().0, 1);
+    /// ```
+    #[allow(clippy::missing_panics_doc)] // the only panic path would be a bug
+    pub fn extension_mut<E: Extension + Clone>(&mut self) -> Option<&mut E> {
+        if !self.inner.extensions.as_ref()?.contains_key(&TypeId::of::<E>()) {
+            return None;
         }
+        let extensions = Arc::make_mut(&mut self.inner).extensions.as_mut()?;
+        let ext = extensions.get_mut(&TypeId::of::<E>())?;
+        if Arc::get_mut(ext).is_none() {
+            let cloned = (&**ext as &dyn Any)
+                .downcast_ref::<E>()
+                .expect("BUG: Extension stored under the wrong TypeId!")
+                .clone();
+            *ext = Arc::new(cloned);
+        }
+        let ext: &mut dyn Any = Arc::get_mut(ext).expect("the extension was just made unique");
+        Some(ext.downcast_mut::<E>().expect("BUG: Extension stored under the wrong TypeId!"))
+    }
+
+    /// Modifies the extension of a given type attached directly to this error, if it exists,
+    /// returning the result of the given function.
+    ///
+    /// See [`ErrorContext::extension_mut`] for details.
+    pub fn update_extension<E, R, F>(&mut self, f: F) -> Option<R>
+    where
+        E: Extension + Clone,
+        F: FnOnce(&mut E) -> R,
+    {
+        self.extension_mut::<E>().map(f)
+    }
+
+    /// Iterates over the extensions attached directly to this error, in no particular order
+    ///
+    /// Yields the [`Extension::type_name`] of each extension along with the extension itself.
+    /// Extensions attached to the causes of this error are not included.
+    pub fn extensions(&self) -> impl Iterator<Item = (&'static str, &dyn Extension)> {
+        visible_extensions(self.inner.extensions.as_ref())
     }
 
     /// Removes an extension of a given type from the error, if it exists.
@@ -96,55 +196,49 @@ impl ErrorContext {
 
     /// Removes an extension of a given type from the error, if it exists, returning the removed
     /// extension.
-    #[allow(clippy::missing_panics_doc)] // the only panic path would be a bug
     pub fn remove_extension<E: Extension>(&mut self) -> Option<Arc<E>> {
-        let mut res = None;
-
-        if let Some(extensions) = self.extensions.as_mut().map(Arc::make_mut) {
-            res = extensions.remove(&TypeId::of::<E>())
-                .map(|ext| Arc::downcast(ext).expect("BUG: Extension stored under the wrong TypeId!"));
-
-            // extension already masked, no need to check cause stack
-            if extensions.contains_key(&TypeId::of::<MaskExtension<E>>()) {
-                println!("c");
-                return res;
-            }
-        }
-
-        if let Some(cause) = &self.cause {
-            if let Some(cause_ext) = cause.find_extension::<E>() {
-                let extensions = Arc::make_mut(self.extensions.get_or_insert_default());
-                extensions.insert(TypeId::of::<MaskExtension<E>>(), MaskExtension::<E>::get());
-                res = res.or(Some(cause_ext));
-            }
-        }
-
-        res
+        let inner = Arc::make_mut(&mut self.inner);
+        let cause = inner.cause.as_deref().map(|cause| cause as &(dyn Error + 'static));
+        remove_extension(&mut inner.extensions, cause)
     }
 }
 
 impl Display for ErrorContext {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.context)
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.inner.context)
     }
 }
 
 impl Debug for ErrorContext {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut iter = self.error_chain();
-        write!(f, "{}", iter.next().expect("first item should exist"))?;
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write_chain(f, self.error_chain())
+    }
+}
 
-        let mut iter = iter.enumerate();
-        if let Some((i, item)) = iter.next() {
-            write!(f, "\n\nCaused by:\n    {i}: {item}")?;
+/// Writes every error in the stack, along with their extensions
+pub(crate) fn write_chain(f: &mut core::fmt::Formatter<'_>, mut iter: ErrorIterator<'_>) -> core::fmt::Result {
+    let first = iter.next().expect("first item should exist");
+    write!(f, "{first}")?;
+    #[cfg(feature = "extensions")]
+    write_extensions(f, first, "    ")?;
 
-            for (i, item) in iter {
-                write!(f, "\n    {i}: {item}")?;
-            }
-        }
+    let mut causes = iter.by_ref().enumerate();
+    if let Some((i, item)) = causes.next() {
+        write!(f, "\n\nCaused by:\n    {i}: {item}")?;
+        #[cfg(feature = "extensions")]
+        write_extensions(f, item, "       ")?;
 
-        Ok(())
+        for (i, item) in causes {
+            write!(f, "\n    {i}: {item}")?;
+            #[cfg(feature = "extensions")]
+            write_extensions(f, item, "       ")?;
+        }
+    }
+    if let Some(truncation) = iter.truncation() {
+        write!(f, "\n    {truncation}")?;
     }
+
+    Ok(())
 }
 
 /// `ErrorContext` values are compared using pointer equality.
@@ -153,14 +247,24 @@ impl Debug for ErrorContext {
 /// calls will not be equal, but two clones of the same error will be equal.
 ///
 /// Errors without a cause and extensions, created using `&'static str` are an exception to this.
+///
+/// Wrap the error in [`crate::ByContent`] to compare errors by their messages instead.
 impl PartialEq for ErrorContext {
     fn eq(&self, other: &Self) -> bool {
+        let (this, other) = (&*self.inner, &*other.inner);
+        if ptr::eq(this, other) {
+            return true;
+        }
         let mut result = true;
-        result &= self.context == other.context;
-        result &= option_ptr_eq(&self.cause, &other.cause);
+        result &= this.context == other.context;
+        result &= option_ptr_eq(&this.cause, &other.cause);
         #[cfg(feature = "extensions")]
         {
-            result &= option_ptr_eq(&self.extensions, &other.extensions);
+            result &= match (&this.extensions, &other.extensions) {
+                (None, None) => true,
+                (Some(a), Some(b)) => a.ptr_eq(b),
+                _ => false,
+            };
         }
         result
     }
@@ -169,7 +273,7 @@ impl Eq for ErrorContext {}
 
 impl Error for ErrorContext {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
-        if let Some(cause) = self.cause.as_deref() {
+        if let Some(cause) = self.inner.cause.as_deref() {
             Some(cause)
         } else {
             None
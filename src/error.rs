@@ -0,0 +1,199 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use alloc::sync::Arc;
+use core::{
+    any::type_name,
+    error::Error as StdError,
+    fmt::{Debug, Display},
+    ops::Deref,
+};
+
+#[cfg(feature = "extensions")]
+use crate::Extension;
+use crate::{context::write_chain, unwrap::unwrapped_type_name, ErrContext, ErrorContext, ErrorIterator, IntoErrorIterator, SharedString};
+
+/// A cloneable error type that any error can be converted into using `?`
+///
+/// Unlike [`ErrorContext`], `Error` implements `From<E>` for every error type, so a bare `?`
+/// works on any `Result` inside a function returning `Result<T, Error>`. The error is wrapped
+/// as-is, without adding a message layer: the error stack starts at the wrapped error.
+///
+/// Like `anyhow::Error`, this type does not implement [`core::error::Error`] itself (which would
+/// conflict with the blanket `From` impl), but it [`Deref`]s to one, and supports everything
+/// [`IntoErrorIterator`] and [`ErrContext`] provide.
+/// ```
+/// # use std::{io, sync::Arc};
+/// # use cloneable_errors::{ErrContext, Error, IntoErrorIterator};
+/// fn read(fail: bool) -> io::Result<u32> {
+///     if fail { Err(io::Error::other("disk on fire")) } else { Ok(1) }
+/// }
+///
+/// fn load() -> Result<u32, Error> {
+///     let value = read(true)?;
+///     Ok(value)
+/// }
+///
+/// let error = load().unwrap_err();
+/// assert_eq!(error.to_string(), "disk on fire");
+/// assert!(error.downcast_ref::<io::Error>().is_some());
+///
+/// let copy = error.clone().context("loading the value");
+/// assert_eq!(copy.error_chain().count(), 2);
+/// assert_eq!(copy.serializable_copy().to_string(), "loading the value");
+/// ```
+#[derive(Clone)]
+pub struct Error {
+    error: Arc<dyn StdError + Send + Sync + 'static>,
+    type_name: &'static str,
+}
+
+impl Error {
+    /// Wraps the given error
+    #[must_use]
+    pub fn new<E>(error: E) -> Self
+    where
+        E: StdError + Send + Sync + 'static,
+    {
+        Error {
+            error: Arc::new(error),
+            type_name: type_name::<E>(),
+        }
+    }
+
+    /// Creates a new error from a message, using [`ErrorContext::new`]
+    #[must_use]
+    pub fn msg<M>(msg: M) -> Self
+    where
+        M: Into<SharedString>,
+    {
+        Error::new(ErrorContext::new(msg))
+    }
+
+    /// Returns the [`core::any::type_name`] of the wrapped error
+    #[must_use]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns the wrapped error
+    #[must_use]
+    pub fn into_inner(self) -> Arc<dyn StdError + Send + Sync + 'static> {
+        self.error
+    }
+}
+
+#[cfg(feature = "extensions")]
+impl Error {
+    /// Adds an extension to the error, and returns the modified error.
+    ///
+    /// See [`Error::add_extension`].
+    #[must_use]
+    pub fn with_extension(mut self, ext: Arc<dyn Extension>) -> Self {
+        self.add_extension(ext);
+        self
+    }
+
+    /// Adds an extension to the error, without adding a message layer.
+    ///
+    /// The extension is attached to the wrapped [`ErrorContext`]. Other errors can't hold
+    /// extensions, so they are converted into an [`ErrorContext`] first, which flattens them (see
+    /// the [`From`] impl). Use [`ErrContext::context_with_ext`] to keep them intact instead.
+    pub fn add_extension(&mut self, ext: Arc<dyn Extension>) {
+        let mut error = ErrorContext::from(self.clone());
+        error.add_extension(ext);
+        *self = Error::new(error);
+    }
+}
+
+/// Errors wrapping an [`ErrorContext`] (like the ones created by [`Error::msg`] or
+/// [`crate::bail!`]) are unwrapped without any changes.
+///
+/// <div class="warning">
+///
+/// NOTE: Other errors are flattened into a stack of string errors! Extracting the error
+///       type-specific data that is not exposed in the [`Display`] impl will not be possible!
+///       Use [`ErrContext::context`] to keep the original error.
+///
+/// </div>
+impl From<Error> for ErrorContext {
+    fn from(value: Error) -> Self {
+        if let Some(error) = value.downcast_ref::<ErrorContext>() {
+            return error.clone();
+        }
+        let flattened = value.serializable_copy();
+        ErrorContext::from_parts(
+            flattened.context,
+            flattened
+                .cause
+                .map(|arc| arc as Arc<dyn StdError + Send + Sync + 'static>),
+            None,
+        )
+    }
+}
+
+impl<E> From<E> for Error
+where
+    E: StdError + Send + Sync + 'static,
+{
+    fn from(error: E) -> Self {
+        Error::new(error)
+    }
+}
+
+impl Deref for Error {
+    type Target = dyn StdError + Send + Sync + 'static;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.error
+    }
+}
+
+impl AsRef<dyn StdError + Send + Sync + 'static> for Error {
+    fn as_ref(&self) -> &(dyn StdError + Send + Sync + 'static) {
+        &*self.error
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&*self.error, f)
+    }
+}
+
+/// Formatted like the [`Debug`] output of [`ErrorContext`], listing every cause.
+impl Debug for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write_chain(f, self.error_chain())
+    }
+}
+
+/// `Error` values are compared using pointer equality, like [`ErrorContext`].
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.error, &other.error)
+    }
+}
+impl Eq for Error {}
+
+impl IntoErrorIterator for Error {
+    fn error_chain(&self) -> ErrorIterator<'_> {
+        ErrorIterator::from(&*self.error as &(dyn StdError + 'static))
+    }
+}
+
+/// The wrapped error becomes the cause of the new [`ErrorContext`], without any intermediate
+/// layers.
+impl ErrContext for Error {
+    fn context<M>(self, msg: M) -> ErrorContext
+    where
+        M: Into<SharedString>,
+    {
+        let type_name = unwrapped_type_name(&*self.error, self.type_name);
+        ErrorContext::from_parts(msg.into(), Some(self.error), type_name)
+    }
+}
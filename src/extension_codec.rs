@@ -0,0 +1,182 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+// codecs can only be registered with std, and are only used by serde and bincode
+#![cfg_attr(not(all(feature = "std", any(feature = "serde", feature = "bincode"))), allow(dead_code))]
+
+#[cfg(any(feature = "std", feature = "serde", feature = "bincode"))]
+use alloc::vec::Vec;
+use alloc::{format, string::String, sync::Arc};
+use core::{
+    any::{Any, TypeId},
+    fmt::{Display, Formatter},
+};
+#[cfg(feature = "std")]
+use std::sync::RwLock;
+
+use crate::Extension;
+#[cfg(any(feature = "serde", feature = "bincode"))]
+use crate::{serializable::EncodedExtension, ExtensionMap};
+
+/// An [`Extension`] that is kept when a [`crate::SerializableError`] is serialized with serde or
+/// bincode
+///
+/// The extension is encoded as a string, stored along with its tag. When decoding, the tag is
+/// used to find the decoder of the extension, which has to be registered with
+/// [`register_extension`] first. Extensions that don't implement this trait, or whose tag is not
+/// registered, are dropped. Masks (see [`crate::ErrorContext::remove_extension`]) of registered
+/// extensions are kept as well.
+///
+/// [`Extension::tag`] must return [`SerializableExtension::TAG`] for the extension to be encoded.
+/// `#[derive(Extension)]` implements both when given `#[extension(tag = "...", serialize)]`,
+/// using the [`Display`] and [`core::str::FromStr`] impls of the type.
+/// ```
+/// # use std::fmt::Formatter;
+/// # use cloneable_errors::{Extension, SerializableExtension};
+/// #[derive(Debug, PartialEq, Eq)]
+/// struct HttpStatus(u16);
+/// impl Extension for HttpStatus {
+///     fn tag(&self) -> Option<&'static str> {
+///         Some(Self::TAG)
+///     }
+///     fn is_copyable(&self) -> bool {
+///         true
+///     }
+/// }
+/// impl SerializableExtension for HttpStatus {
+///     const TAG: &'static str = "http_status";
+///     fn encode(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "{}", self.0)
+///     }
+///     fn decode(data: &str) -> Option<Self> {
+///         data.parse().ok().map(HttpStatus)
+///     }
+/// }
+///
+/// # #[cfg(feature = "std")]
+/// cloneable_errors::register_extension::<HttpStatus>();
+/// ```
+pub trait SerializableExtension: Extension + Sized {
+    /// The stable name of the extension, stored along with its encoded form
+    const TAG: &'static str;
+
+    /// Writes the encoded form of the extension
+    #[allow(clippy::missing_errors_doc)]
+    fn encode(&self, f: &mut Formatter<'_>) -> core::fmt::Result;
+
+    /// Decodes an extension written by [`SerializableExtension::encode`]
+    ///
+    /// Returning [`None`] drops the extension.
+    fn decode(data: &str) -> Option<Self>;
+}
+
+#[derive(Clone, Copy)]
+struct Codec {
+    tag: &'static str,
+    type_id: TypeId,
+    encode: fn(&dyn Extension) -> Option<String>,
+    decode: fn(&str) -> Option<Arc<dyn Extension>>,
+}
+
+#[cfg(feature = "std")]
+static CODECS: RwLock<Vec<Codec>> = RwLock::new(Vec::new());
+
+/// Allows a [`SerializableExtension`] to be serialized along with [`crate::SerializableError`]s
+///
+/// Both the serializing and the deserializing side have to register the extension. Registering
+/// an extension with the tag of a previously registered one replaces it.
+///
+/// Requires the `std` feature.
+///
+/// # Panics
+/// Will panic if the internal lock was poisoned.
+#[cfg(feature = "std")]
+pub fn register_extension<E: SerializableExtension>() {
+    let mut codecs = CODECS.write().expect("Internal lock got poisoned");
+    codecs.retain(|codec| codec.tag != E::TAG);
+    codecs.push(Codec {
+        tag: E::TAG,
+        type_id: TypeId::of::<E>(),
+        encode: encode_as::<E>,
+        decode: decode_as::<E>,
+    });
+}
+
+/// Calls the given function with the registered codecs
+fn with_codecs<R>(f: impl FnOnce(&[Codec]) -> R) -> R {
+    #[cfg(feature = "std")]
+    {
+        f(&CODECS.read().expect("Internal lock got poisoned"))
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        f(&[])
+    }
+}
+
+/// Displays an extension using [`SerializableExtension::encode`]
+struct Encoded<'a, E>(&'a E);
+
+impl<E: SerializableExtension> Display for Encoded<'_, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        self.0.encode(f)
+    }
+}
+
+fn encode_as<E: SerializableExtension>(ext: &dyn Extension) -> Option<String> {
+    let ext = (ext as &dyn Any).downcast_ref::<E>()?;
+    Some(format!("{}", Encoded(ext)))
+}
+
+fn decode_as<E: SerializableExtension>(data: &str) -> Option<Arc<dyn Extension>> {
+    E::decode(data).map(|ext| Arc::new(ext) as Arc<dyn Extension>)
+}
+
+/// Encodes the registered extensions and masks of a single error, sorted by tag
+#[cfg(any(feature = "serde", feature = "bincode"))]
+pub(crate) fn encode_extensions(map: Option<&ExtensionMap>) -> Vec<EncodedExtension> {
+    let Some(map) = map else { return Vec::new() };
+    let mut encoded: Vec<_> = with_codecs(|codecs| {
+        map.entries()
+            .flat_map(|(key, ext, masks)| {
+                let value = ext.and_then(|ext| {
+                    let codec = codecs.iter().find(|codec| ext.tag() == Some(codec.tag))?;
+                    Some((codec.tag.into(), Some((codec.encode)(&**ext)?)))
+                });
+                let mask = masks
+                    .then(|| codecs.iter().find(|codec| codec.type_id == *key))
+                    .flatten()
+                    .map(|codec| (codec.tag.into(), None));
+                value.into_iter().chain(mask)
+            })
+            .collect()
+    });
+    encoded.sort_unstable();
+    encoded
+}
+
+/// Decodes the extensions and masks of a single error, dropping the ones that are not registered
+#[cfg(any(feature = "serde", feature = "bincode"))]
+pub(crate) fn decode_extensions(encoded: Vec<EncodedExtension>) -> Option<ExtensionMap> {
+    if encoded.is_empty() {
+        return None;
+    }
+    let mut map = ExtensionMap::default();
+    with_codecs(|codecs| {
+        for (tag, data) in encoded {
+            let Some(codec) = codecs.iter().find(|codec| codec.tag == tag) else { continue };
+            match data {
+                Some(data) => {
+                    if let Some(ext) = (codec.decode)(&data) {
+                        map.insert(ext);
+                    }
+                }
+                None => map.mask(codec.type_id),
+            }
+        }
+    });
+    (map.len() > 0).then_some(map)
+}
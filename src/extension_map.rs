@@ -0,0 +1,416 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use alloc::{sync::Arc, vec, vec::Vec};
+use core::{any::TypeId, cmp::Ordering, ops::Deref};
+
+use crate::{iterator::ARC_OVERHEAD, Extension};
+
+/// Maps with at most this many entries are stored as a flat array
+const SMALL_CAPACITY: usize = 4;
+
+type Entry = (TypeId, Slot);
+type Link = Option<Arc<Node>>;
+
+/// The extensions attached to a single error
+///
+/// Besides extensions, the map records which extension types are masked by
+/// [`crate::ErrorContext::remove_extension`], hiding instances attached to deeper errors.
+///
+/// The map is persistent: cloning it is cheap, and modifying a clone only copies the parts of
+/// the map that changed, leaving the rest shared with the original. Maps with up to 4 entries are
+/// stored as a flat array and searched linearly, larger maps are stored as a balanced tree.
+///
+/// Use the extension methods of [`crate::ErrorContext`] and [`crate::SerializableError`] to
+/// inspect and modify it.
+#[derive(Clone)]
+pub struct ExtensionMap {
+    repr: Repr,
+}
+
+#[derive(Clone)]
+enum Repr {
+    Small(Arc<[Entry]>),
+    Tree { root: Arc<Node>, len: usize },
+}
+
+/// The state of a single extension type in an [`ExtensionMap`]
+#[derive(Clone)]
+struct Slot {
+    /// The extension attached to this error
+    value: Option<Arc<dyn Extension>>,
+    /// Whether instances of this type attached to deeper errors are hidden
+    masks: bool,
+}
+
+/// A node of a persistent AVL tree
+#[derive(Clone)]
+struct Node {
+    key: TypeId,
+    value: Slot,
+    left: Link,
+    right: Link,
+    height: u8,
+}
+
+impl Default for ExtensionMap {
+    fn default() -> Self {
+        ExtensionMap {
+            repr: Repr::Small(Arc::new([])),
+        }
+    }
+}
+
+impl ExtensionMap {
+    /// Returns the number of entries in the map
+    pub(crate) fn len(&self) -> usize {
+        match &self.repr {
+            Repr::Small(entries) => entries.len(),
+            Repr::Tree { len, .. } => *len,
+        }
+    }
+
+    /// Returns true if both maps share the same storage
+    pub(crate) fn ptr_eq(&self, other: &ExtensionMap) -> bool {
+        match (&self.repr, &other.repr) {
+            (Repr::Small(a), Repr::Small(b)) => Arc::ptr_eq(a, b),
+            (Repr::Tree { root: a, .. }, Repr::Tree { root: b, .. }) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    fn slot(&self, key: &TypeId) -> Option<&Slot> {
+        match &self.repr {
+            Repr::Small(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            Repr::Tree { root, .. } => {
+                let mut node = root;
+                loop {
+                    let next = match key.cmp(&node.key) {
+                        Ordering::Less => &node.left,
+                        Ordering::Greater => &node.right,
+                        Ordering::Equal => return Some(&node.value),
+                    };
+                    node = next.as_ref()?;
+                }
+            }
+        }
+    }
+
+    /// Returns a mutable reference to the slot with the given key, copying any shared parts of
+    /// the map on the way to it
+    fn slot_mut(&mut self, key: &TypeId) -> Option<&mut Slot> {
+        self.slot(key)?;
+        match &mut self.repr {
+            Repr::Small(entries) => {
+                if Arc::get_mut(entries).is_none() {
+                    *entries = entries.iter().cloned().collect();
+                }
+                Arc::get_mut(entries)?
+                    .iter_mut()
+                    .find(|(k, _)| k == key)
+                    .map(|(_, v)| v)
+            }
+            Repr::Tree { root, .. } => {
+                let mut node = Arc::make_mut(root);
+                loop {
+                    let next = match key.cmp(&node.key) {
+                        Ordering::Less => &mut node.left,
+                        Ordering::Greater => &mut node.right,
+                        Ordering::Equal => return Some(&mut node.value),
+                    };
+                    node = Arc::make_mut(next.as_mut()?);
+                }
+            }
+        }
+    }
+
+    /// Replaces the slot with the given key, returning the previous one
+    fn set_slot(&mut self, key: TypeId, slot: Slot) -> Option<Slot> {
+        match &mut self.repr {
+            Repr::Small(entries) => {
+                if let Some(i) = entries.iter().position(|(k, _)| *k == key) {
+                    let mut copy = entries.to_vec();
+                    let old = core::mem::replace(&mut copy[i].1, slot);
+                    *entries = copy.into();
+                    return Some(old);
+                }
+                if entries.len() < SMALL_CAPACITY {
+                    *entries = entries.iter().cloned().chain([(key, slot)]).collect();
+                    return None;
+                }
+                let mut root = None;
+                for (k, v) in entries.iter().cloned().chain([(key, slot)]) {
+                    root = Some(insert(root.as_ref(), k, v).0);
+                }
+                self.repr = Repr::Tree {
+                    root: root.expect("the map is not empty"),
+                    len: SMALL_CAPACITY + 1,
+                };
+                None
+            }
+            Repr::Tree { root, len } => {
+                let (new_root, old) = insert(Some(root), key, slot);
+                *root = new_root;
+                if old.is_none() {
+                    *len += 1;
+                }
+                old
+            }
+        }
+    }
+
+    /// Removes the slot with the given key, returning it
+    fn remove_slot(&mut self, key: &TypeId) -> Option<Slot> {
+        match &mut self.repr {
+            Repr::Small(entries) => {
+                let i = entries.iter().position(|(k, _)| k == key)?;
+                let mut copy = entries.to_vec();
+                let (_, old) = copy.remove(i);
+                *entries = copy.into();
+                Some(old)
+            }
+            Repr::Tree { root, len } => {
+                let (new_root, old) = remove(root, key)?;
+                *len -= 1;
+                self.repr = match new_root {
+                    Some(root) if *len > SMALL_CAPACITY => Repr::Tree { root, len: *len },
+                    root => {
+                        let mut entries = Vec::with_capacity(*len);
+                        if let Some(root) = &root {
+                            collect(root, &mut entries);
+                        }
+                        Repr::Small(entries.into())
+                    }
+                };
+                Some(old)
+            }
+        }
+    }
+
+    /// Returns the extension of the given type attached to this error
+    pub(crate) fn get(&self, key: &TypeId) -> Option<&Arc<dyn Extension>> {
+        self.slot(key)?.value.as_ref()
+    }
+
+    pub(crate) fn contains_key(&self, key: &TypeId) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns true if instances of the given type attached to deeper errors are hidden
+    pub(crate) fn is_masked(&self, key: &TypeId) -> bool {
+        self.slot(key).is_some_and(|slot| slot.masks)
+    }
+
+    /// Returns a mutable reference to the extension of the given type, copying any shared parts of
+    /// the map on the way to it
+    pub(crate) fn get_mut(&mut self, key: &TypeId) -> Option<&mut Arc<dyn Extension>> {
+        self.get(key)?;
+        self.slot_mut(key)?.value.as_mut()
+    }
+
+    /// Inserts an extension under its own type id, returning the replaced extension
+    pub(crate) fn insert(&mut self, ext: Arc<dyn Extension>) -> Option<Arc<dyn Extension>> {
+        let key = ext.deref().type_id();
+        let masks = self.is_masked(&key);
+        self.set_slot(key, Slot { value: Some(ext), masks })?.value
+    }
+
+    /// Removes the extension of the given type, returning it
+    ///
+    /// The mask for the type stays in place, if there is one.
+    pub(crate) fn remove(&mut self, key: &TypeId) -> Option<Arc<dyn Extension>> {
+        self.get(key)?;
+        if self.is_masked(key) {
+            self.slot_mut(key)?.value.take()
+        } else {
+            self.remove_slot(key)?.value
+        }
+    }
+
+    /// Hides instances of the given type attached to deeper errors
+    pub(crate) fn mask(&mut self, key: TypeId) {
+        match self.slot_mut(&key) {
+            Some(slot) => slot.masks = true,
+            None => {
+                self.set_slot(key, Slot { value: None, masks: true });
+            }
+        }
+    }
+
+    /// Creates a new map with the masks of this map, and the extensions matching the predicate
+    pub(crate) fn filter(&self, mut predicate: impl FnMut(&dyn Extension) -> bool) -> ExtensionMap {
+        let mut map = ExtensionMap::default();
+        for (key, slot) in self.slots() {
+            let value = slot.value.clone().filter(|ext| predicate(&**ext));
+            if value.is_some() || slot.masks {
+                map.set_slot(*key, Slot { value, masks: slot.masks });
+            }
+        }
+        map
+    }
+
+    /// Iterates over the slots of the map, in no particular order
+    fn slots(&self) -> Slots<'_> {
+        match &self.repr {
+            Repr::Small(entries) => Slots::Small(entries.iter()),
+            Repr::Tree { root, .. } => Slots::Tree(vec![root]),
+        }
+    }
+
+    /// Iterates over the extensions in the map, in no particular order
+    pub(crate) fn values(&self) -> impl Iterator<Item = &Arc<dyn Extension>> {
+        self.slots().filter_map(|(_, slot)| slot.value.as_ref())
+    }
+
+    /// Iterates over the entries of the map, in no particular order
+    ///
+    /// Yields the type id, the extension (if one is attached) and whether the type is masked.
+    #[cfg(any(feature = "serde", feature = "bincode"))]
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (&TypeId, Option<&Arc<dyn Extension>>, bool)> {
+        self.slots().map(|(key, slot)| (key, slot.value.as_ref(), slot.masks))
+    }
+
+    /// Estimates the heap memory used by the map, in bytes
+    pub(crate) fn estimated_size(&self) -> usize {
+        match &self.repr {
+            Repr::Small(entries) => ARC_OVERHEAD + size_of_val::<[Entry]>(entries),
+            Repr::Tree { len, .. } => len * (ARC_OVERHEAD + size_of::<Node>()),
+        }
+    }
+}
+
+/// An iterator over the slots of an [`ExtensionMap`]
+enum Slots<'a> {
+    Small(core::slice::Iter<'a, Entry>),
+    /// Nodes (with their subtrees) that are yet to be visited
+    Tree(Vec<&'a Arc<Node>>),
+}
+
+impl<'a> Iterator for Slots<'a> {
+    type Item = (&'a TypeId, &'a Slot);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Slots::Small(entries) => entries.next().map(|(k, v)| (k, v)),
+            Slots::Tree(stack) => {
+                let node = stack.pop()?;
+                stack.extend(node.left.iter().chain(node.right.iter()));
+                Some((&node.key, &node.value))
+            }
+        }
+    }
+}
+
+fn height(link: &Link) -> u8 {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+fn node(key: TypeId, value: Slot, left: Link, right: Link) -> Arc<Node> {
+    let height = height(&left).max(height(&right)) + 1;
+    Arc::new(Node { key, value, left, right, height })
+}
+
+/// Creates a new node, rotating it if the heights of its subtrees differ by more than one
+fn balance(key: TypeId, value: Slot, left: Link, right: Link) -> Arc<Node> {
+    let (hl, hr) = (height(&left), height(&right));
+    if hl > hr + 1 {
+        let l = left.expect("the left subtree is higher than the right one");
+        if height(&l.left) >= height(&l.right) {
+            node(l.key, l.value.clone(), l.left.clone(), Some(node(key, value, l.right.clone(), right)))
+        } else {
+            let lr = l.right.as_ref().expect("the right subtree is higher than the left one");
+            node(
+                lr.key,
+                lr.value.clone(),
+                Some(node(l.key, l.value.clone(), l.left.clone(), lr.left.clone())),
+                Some(node(key, value, lr.right.clone(), right)),
+            )
+        }
+    } else if hr > hl + 1 {
+        let r = right.expect("the right subtree is higher than the left one");
+        if height(&r.right) >= height(&r.left) {
+            node(r.key, r.value.clone(), Some(node(key, value, left, r.left.clone())), r.right.clone())
+        } else {
+            let rl = r.left.as_ref().expect("the left subtree is higher than the right one");
+            node(
+                rl.key,
+                rl.value.clone(),
+                Some(node(key, value, left, rl.left.clone())),
+                Some(node(r.key, r.value.clone(), rl.right.clone(), r.right.clone())),
+            )
+        }
+    } else {
+        node(key, value, left, right)
+    }
+}
+
+/// Inserts an entry into a subtree, returning the new subtree and the replaced value
+fn insert(link: Option<&Arc<Node>>, key: TypeId, value: Slot) -> (Arc<Node>, Option<Slot>) {
+    let Some(n) = link else {
+        return (node(key, value, None, None), None);
+    };
+    match key.cmp(&n.key) {
+        Ordering::Less => {
+            let (left, old) = insert(n.left.as_ref(), key, value);
+            (balance(n.key, n.value.clone(), Some(left), n.right.clone()), old)
+        }
+        Ordering::Greater => {
+            let (right, old) = insert(n.right.as_ref(), key, value);
+            (balance(n.key, n.value.clone(), n.left.clone(), Some(right)), old)
+        }
+        Ordering::Equal => (
+            node(key, value, n.left.clone(), n.right.clone()),
+            Some(n.value.clone()),
+        ),
+    }
+}
+
+/// Removes an entry from a subtree, returning the new subtree and the removed value
+fn remove(n: &Arc<Node>, key: &TypeId) -> Option<(Link, Slot)> {
+    match key.cmp(&n.key) {
+        Ordering::Less => {
+            let (left, old) = remove(n.left.as_ref()?, key)?;
+            Some((Some(balance(n.key, n.value.clone(), left, n.right.clone())), old))
+        }
+        Ordering::Greater => {
+            let (right, old) = remove(n.right.as_ref()?, key)?;
+            Some((Some(balance(n.key, n.value.clone(), n.left.clone(), right)), old))
+        }
+        Ordering::Equal => {
+            let subtree = match (&n.left, &n.right) {
+                (None, right) => right.clone(),
+                (left, None) => left.clone(),
+                (left, Some(right)) => {
+                    let (right, min_key, min_value) = remove_min(right);
+                    Some(balance(min_key, min_value, left.clone(), right))
+                }
+            };
+            Some((subtree, n.value.clone()))
+        }
+    }
+}
+
+/// Removes the smallest entry from a subtree, returning the new subtree and the removed entry
+fn remove_min(n: &Arc<Node>) -> (Link, TypeId, Slot) {
+    match &n.left {
+        None => (n.right.clone(), n.key, n.value.clone()),
+        Some(left) => {
+            let (left, key, value) = remove_min(left);
+            (Some(balance(n.key, n.value.clone(), left, n.right.clone())), key, value)
+        }
+    }
+}
+
+fn collect(n: &Arc<Node>, entries: &mut Vec<Entry>) {
+    if let Some(left) = &n.left {
+        collect(left, entries);
+    }
+    entries.push((n.key, n.value.clone()));
+    if let Some(right) = &n.right {
+        collect(right, entries);
+    }
+}
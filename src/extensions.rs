@@ -5,42 +5,229 @@
 * Copyright (C) 2025 mini_bomba
 */
 
-use std::{
+use alloc::{sync::Arc, vec::Vec};
+use core::{
     any::{Any, TypeId},
-    collections::HashMap,
+    error::Error,
+    fmt::{Debug, Display},
     marker::PhantomData,
-    sync::{Arc, LazyLock, Mutex},
 };
 
+use crate::{ErrorContext, ErrorIterator, ExtensionMap, SerializableError};
+
 /// This trait should be implemented by any structs
 /// that are intended to be used as error extensions.
-pub trait Extension: 'static + Send + Sync + Any {}
+///
+/// Extensions are not shown in the [`Debug`] output of [`ErrorContext`] by default. To have an
+/// extension rendered under the error it's attached to, override [`Extension::as_display`] or
+/// [`Extension::as_debug`]:
+/// ```
+/// # use std::{fmt::Debug, sync::Arc};
+/// # use cloneable_errors::{anyhow, Extension};
+/// #[derive(Debug)]
+/// struct HttpStatus(u16);
+/// impl Extension for HttpStatus {
+///     fn as_debug(&self) -> Option<&dyn Debug> {
+///         Some(self)
+///     }
+/// }
+///
+/// let error = anyhow!("request failed", extend: Arc::new(HttpStatus(404)));
+/// assert_eq!(format!("{error:?}"), "request failed\n    - HttpStatus(404)");
+/// ```
+pub trait Extension: 'static + Send + Sync + Any {
+    /// Returns the name of the extension type, as returned by [`std::any::type_name`]
+    fn type_name(&self) -> &'static str {
+        core::any::type_name::<Self>()
+    }
+
+    /// Returns a stable name identifying this extension type, if it has one
+    ///
+    /// Unlike [`Extension::type_name`], this name is chosen by the author of the extension and
+    /// doesn't change between builds, which makes it suitable for exporters and serializers that
+    /// need to refer to extension types outside of the process. Returns [`None`] by default.
+    fn tag(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Returns true if this extension should be carried over by
+    /// [`crate::IntoErrorIterator::serializable_copy`]
+    ///
+    /// Only the extension is copied, no serialization takes place. Returns false by default.
+    fn is_copyable(&self) -> bool {
+        false
+    }
+
+    /// Returns this extension as [`Debug`], if it should be rendered in the [`Debug`] output of
+    /// errors
+    ///
+    /// Returns [`None`] by default. Ignored if [`Extension::as_display`] returns [`Some`].
+    fn as_debug(&self) -> Option<&dyn Debug> {
+        None
+    }
+
+    /// Returns this extension as [`Display`], if it should be rendered in the [`Debug`] output of
+    /// errors
+    ///
+    /// Returns [`None`] by default.
+    fn as_display(&self) -> Option<&dyn Display> {
+        None
+    }
+}
+
+/// An [`Extension`] whose instances along an error stack can be combined into a single value
+///
+/// Used by [`crate::IntoErrorIterator::merged_extension`].
+/// ```
+/// # use std::{collections::BTreeSet, sync::Arc};
+/// # use cloneable_errors::{anyhow, ErrContext, Extension, IntoErrorIterator, MergeExtension};
+/// struct Tags(BTreeSet<&'static str>);
+/// impl Extension for Tags {}
+/// impl MergeExtension for Tags {
+///     fn merge(&self, deeper: &Self) -> Self {
+///         Tags(self.0.union(&deeper.0).copied().collect())
+///     }
+/// }
+///
+/// let error = anyhow!("inner", extend: Arc::new(Tags(["db"].into())))
+///     .context("outer")
+///     .with_extension(Arc::new(Tags(["api", "retry"].into())));
+/// let tags = error.merged_extension::<Tags>().unwrap();
+/// assert_eq!(tags.0, ["api", "db", "retry"].into());
+/// ```
+pub trait MergeExtension: Extension + Sized {
+    /// Combines this instance with an instance found deeper in the error stack
+    ///
+    /// When more than two instances are present, `deeper` is the result of merging all deeper
+    /// instances.
+    #[must_use]
+    fn merge(&self, deeper: &Self) -> Self;
+}
+
+/// Iterates over the extensions in a map
+pub(crate) fn visible_extensions(
+    map: Option<&ExtensionMap>,
+) -> impl Iterator<Item = (&'static str, &dyn Extension)> {
+    map.into_iter()
+        .flat_map(ExtensionMap::values)
+        .map(|ext| (ext.type_name(), &**ext))
+}
+
+/// Writes the extensions of a single layer that opted into being rendered, sorted by type name
+pub(crate) fn write_extensions(
+    f: &mut core::fmt::Formatter<'_>,
+    err: &(dyn Error + 'static),
+    indent: &str,
+) -> core::fmt::Result {
+    let mut extensions: Vec<_> = visible_extensions(layer_extensions(err)).collect();
+    extensions.sort_unstable_by_key(|(name, _)| *name);
+    for (_, ext) in extensions {
+        if let Some(ext) = ext.as_display() {
+            write!(f, "\n{indent}- {ext}")?;
+        } else if let Some(ext) = ext.as_debug() {
+            write!(f, "\n{indent}- {ext:?}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the extensions attached directly to the given error, if it's one of our types
+pub(crate) fn layer_extensions<'a>(err: &'a (dyn Error + 'static)) -> Option<&'a ExtensionMap> {
+    if let Some(err) = err.downcast_ref::<ErrorContext>() {
+        err.extension_map()
+    } else if let Some(err) = err.downcast_ref::<SerializableError>() {
+        err.extension_map()
+    } else {
+        None
+    }
+}
+
+/// Adds an extension to a map, overwriting any previous instance of its type
+pub(crate) fn insert_extension(map: &mut Option<ExtensionMap>, ext: Arc<dyn Extension>) {
+    map.get_or_insert_default().insert(ext);
+}
+
+/// Removes an extension of a given type from a map, masking any instances found in the cause
+/// stack, returning the removed extension.
+#[allow(clippy::missing_panics_doc)] // the only panic path would be a bug
+pub(crate) fn remove_extension<E: Extension>(
+    map: &mut Option<ExtensionMap>,
+    cause: Option<&(dyn Error + 'static)>,
+) -> Option<Arc<E>> {
+    let mut res = None;
 
-pub(crate) type ExtensionMap = Arc<HashMap<TypeId, Arc<dyn Extension>>>;
+    if let Some(extensions) = map.as_mut() {
+        res = extensions.remove(&TypeId::of::<E>())
+            .map(|ext| Arc::downcast(ext).expect("BUG: Extension stored under the wrong TypeId!"));
 
-static MASK_CACHE: LazyLock<Mutex<HashMap<TypeId, Arc<dyn Extension>>>> =
-    LazyLock::new(Mutex::default);
+        // extension already masked, no need to check cause stack
+        if extensions.is_masked(&TypeId::of::<E>()) {
+            return res;
+        }
+    }
+
+    if let Some(cause) = cause {
+        if let Some(cause_ext) = ErrorIterator::from(cause).find_extension::<E>() {
+            map.get_or_insert_default().mask(TypeId::of::<E>());
+            res = res.or(Some(cause_ext));
+        }
+    }
+
+    res
+}
 
-/// A special type that masks any extesions of a given
-/// type that may exist deeper along the cause chain
-/// from being seen in higher-level errors.
-#[derive(Clone, Copy, Debug)]
-pub(crate) struct MaskExtension<T: Extension + ?Sized> {
-    _phantom: PhantomData<T>,
+/// Copies the extensions marked as copyable from the given error
+pub(crate) fn copyable_extensions(err: &(dyn Error + 'static)) -> Option<ExtensionMap> {
+    // masks are always copied, as they must follow the extensions they hide
+    let copied = layer_extensions(err)?.filter(Extension::is_copyable);
+    (copied.len() > 0).then_some(copied)
 }
-impl<T: Extension + ?Sized> Extension for MaskExtension<T> {}
 
-impl<T: Extension + ?Sized> MaskExtension<T> {
-    pub(crate) fn get() -> Arc<dyn Extension> {
-        MASK_CACHE
-            .lock()
-            .expect("Internal lock got poisoned")
-            .entry(TypeId::of::<T>())
-            .or_insert_with(|| {
-                Arc::new(MaskExtension {
-                    _phantom: PhantomData::<T>,
-                })
-            })
-            .clone()
+/// Retrieves the extension of a given type from a single extension map
+#[allow(clippy::missing_panics_doc)] // the only panic path would be a bug
+pub(crate) fn get_extension<E: Extension>(map: &ExtensionMap) -> Option<Arc<E>> {
+    map.get(&TypeId::of::<E>())
+        .cloned()
+        .map(|ext| Arc::downcast(ext).expect("BUG: Extension stored under the wrong TypeId!"))
+}
+
+/// An iterator over every instance of a given extension type in an error stack, starting from
+/// the most recent one
+///
+/// Created by [`ErrorIterator::find_all_extensions`]. Stops at the first layer masking the
+/// extension type (see [`ErrorContext::remove_extension`]).
+pub struct ExtensionInstances<'a, E: Extension> {
+    errors: Option<ErrorIterator<'a>>,
+    _phantom: PhantomData<fn() -> E>,
+}
+
+impl<'a, E: Extension> ExtensionInstances<'a, E> {
+    pub(crate) fn new(errors: ErrorIterator<'a>) -> Self {
+        ExtensionInstances {
+            errors: Some(errors),
+            _phantom: PhantomData,
+        }
     }
 }
+
+impl<E: Extension> Iterator for ExtensionInstances<'_, E> {
+    type Item = Arc<E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(err) = self.errors.as_mut()?.next() {
+            let Some(map) = layer_extensions(err) else { continue };
+            if map.is_masked(&TypeId::of::<E>()) {
+                // found a mask matching the requested extension,
+                // instances in deeper layers are hidden
+                self.errors = None;
+            }
+            if let Some(ext) = get_extension(map) {
+                return Some(ext);
+            }
+        }
+        self.errors = None;
+        None
+    }
+}
+
+impl<E: Extension> core::iter::FusedIterator for ExtensionInstances<'_, E> {}
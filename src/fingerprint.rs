@@ -0,0 +1,284 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use alloc::{
+    borrow::Cow,
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Display;
+#[cfg(feature = "std")]
+use std::sync::LazyLock;
+
+use crate::{ErrorIterator, ErrorContext, SerializableError};
+
+const FNV_OFFSET: u128 = 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d;
+const FNV_PRIME: u128 = 0x0000_0000_0100_0000_0000_0000_0000_013b;
+
+/// A stable 128-bit hash of an error chain, computed by [`Fingerprinter::fingerprint`]
+///
+/// Fingerprints are stable across processes, platforms and crate versions with the same major
+/// version, making them suitable for grouping errors in dashboards or as cache keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Fingerprint(pub u128);
+
+impl Fingerprint {
+    /// Returns the full 128-bit value of this fingerprint
+    #[must_use]
+    pub fn as_u128(self) -> u128 {
+        self.0
+    }
+
+    /// Returns a 64-bit version of this fingerprint, made by folding the two halves together
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)] // intentional
+    pub fn as_u64(self) -> u64 {
+        (self.0 >> 64) as u64 ^ self.0 as u64
+    }
+}
+
+impl Display for Fingerprint {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+/// A helper trait for removing volatile parts of error messages before they are fingerprinted
+pub trait Normalizer: Send + Sync {
+    /// Replaces any volatile parts of the message with placeholders
+    ///
+    /// Should return [`Cow::Borrowed`] if the message was not changed.
+    fn normalize<'a>(&self, message: &'a str) -> Cow<'a, str>;
+}
+
+/// Replaces every run of ASCII digits with `#`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NumberNormalizer;
+
+impl Normalizer for NumberNormalizer {
+    fn normalize<'a>(&self, message: &'a str) -> Cow<'a, str> {
+        if !message.bytes().any(|b| b.is_ascii_digit()) {
+            return Cow::Borrowed(message);
+        }
+        let mut result = String::with_capacity(message.len());
+        let mut in_number = false;
+        for c in message.chars() {
+            if c.is_ascii_digit() {
+                if !in_number {
+                    result.push('#');
+                }
+                in_number = true;
+            } else {
+                result.push(c);
+                in_number = false;
+            }
+        }
+        Cow::Owned(result)
+    }
+}
+
+/// Replaces every hyphenated UUID (`xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx`) with `<uuid>`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UuidNormalizer;
+
+impl UuidNormalizer {
+    const GROUPS: [usize; 5] = [8, 4, 4, 4, 12];
+    const LENGTH: usize = 36;
+
+    fn is_uuid(candidate: &[u8]) -> bool {
+        let mut pos = 0;
+        for (i, len) in Self::GROUPS.into_iter().enumerate() {
+            if i != 0 {
+                if candidate[pos] != b'-' {
+                    return false;
+                }
+                pos += 1;
+            }
+            if !candidate[pos..pos + len].iter().all(u8::is_ascii_hexdigit) {
+                return false;
+            }
+            pos += len;
+        }
+        true
+    }
+}
+
+impl Normalizer for UuidNormalizer {
+    fn normalize<'a>(&self, message: &'a str) -> Cow<'a, str> {
+        let bytes = message.as_bytes();
+        let mut result = String::new();
+        let mut copied_until = 0;
+        let mut i = 0;
+        while i + Self::LENGTH <= bytes.len() {
+            let bounded_left = i == 0 || !bytes[i - 1].is_ascii_alphanumeric();
+            let bounded_right = bytes.get(i + Self::LENGTH).is_none_or(|b| !b.is_ascii_alphanumeric());
+            if bounded_left && bounded_right && Self::is_uuid(&bytes[i..i + Self::LENGTH]) {
+                // all bytes of an uuid are ASCII, so both ends are char boundaries
+                result.push_str(&message[copied_until..i]);
+                result.push_str("<uuid>");
+                i += Self::LENGTH;
+                copied_until = i;
+            } else {
+                i += 1;
+            }
+        }
+        if copied_until == 0 {
+            return Cow::Borrowed(message);
+        }
+        result.push_str(&message[copied_until..]);
+        Cow::Owned(result)
+    }
+}
+
+/// Replaces every whitespace-separated word that looks like a filesystem path with `<path>`
+///
+/// A word is considered a path if it starts with `/`, `./`, `../`, `~/` or a Windows drive
+/// letter (like `C:\`). Quotes and parentheses surrounding the path, as well as trailing
+/// punctuation, are preserved.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PathNormalizer;
+
+impl PathNormalizer {
+    fn is_path(word: &str) -> bool {
+        let bytes = word.as_bytes();
+        word.starts_with('/')
+            || word.starts_with("./")
+            || word.starts_with("../")
+            || word.starts_with("~/")
+            || (bytes.len() >= 3
+                && bytes[0].is_ascii_alphabetic()
+                && bytes[1] == b':'
+                && matches!(bytes[2], b'\\' | b'/'))
+    }
+}
+
+impl Normalizer for PathNormalizer {
+    fn normalize<'a>(&self, message: &'a str) -> Cow<'a, str> {
+        const LEADING: &[char] = &['"', '\'', '(', '[', '<', '`'];
+        const TRAILING: &[char] = &['"', '\'', ')', ']', '>', '`', ',', ';', ':', '.'];
+
+        let mut result = String::new();
+        let mut changed = false;
+        for word in message.split_inclusive(char::is_whitespace) {
+            let content = word.trim_end_matches(char::is_whitespace);
+            let inner = content.trim_start_matches(LEADING);
+            let path = inner.trim_end_matches(TRAILING);
+            if Self::is_path(path) {
+                changed = true;
+                result.push_str(&content[..content.len() - inner.len()]);
+                result.push_str("<path>");
+                result.push_str(&word[content.len() - inner.len() + path.len()..]);
+            } else {
+                result.push_str(word);
+            }
+        }
+        if changed {
+            Cow::Owned(result)
+        } else {
+            Cow::Borrowed(message)
+        }
+    }
+}
+
+/// Computes [`Fingerprint`]s of error chains using a configurable set of [`Normalizer`]s
+///
+/// The fingerprint is computed from the normalized messages of each error in the chain, which
+/// means that an error and its [`SerializableError`] copy will have the same fingerprint.
+///
+/// The type names of the errors are not hashed. A serializable copy no longer knows the types of
+/// the original errors, so including them would make the fingerprints of the original and its
+/// copy differ. [`core::any::type_name`] is also not guaranteed to be stable between compiler
+/// versions, which would break the stability of fingerprints.
+///
+/// The default configuration replaces UUIDs, paths and numbers, in that order.
+/// ```
+/// # use cloneable_errors::{anyhow, IntoErrorIterator};
+/// let a = anyhow!("connection to 10.0.0.5 failed after {} ms", 1500);
+/// let b = anyhow!("connection to 10.0.0.7 failed after {} ms", 30);
+/// assert_eq!(a.fingerprint(), b.fingerprint());
+/// assert_eq!(a.fingerprint(), a.serializable_copy().fingerprint());
+/// ```
+pub struct Fingerprinter {
+    normalizers: Vec<Box<dyn Normalizer>>,
+}
+
+impl Default for Fingerprinter {
+    /// Creates a new `Fingerprinter` with the default set of normalizers
+    fn default() -> Self {
+        Fingerprinter::new()
+            .with_normalizer(UuidNormalizer)
+            .with_normalizer(PathNormalizer)
+            .with_normalizer(NumberNormalizer)
+    }
+}
+
+/// The default `Fingerprinter`, built once
+#[cfg(feature = "std")]
+static DEFAULT_FINGERPRINTER: LazyLock<Fingerprinter> = LazyLock::new(Fingerprinter::default);
+
+/// Computes the fingerprint of the given error chain with the default `Fingerprinter`
+///
+/// Without std, there's no lazy static to keep it in, so it's built on every call.
+pub(crate) fn default_fingerprint(chain: ErrorIterator<'_>) -> Fingerprint {
+    #[cfg(feature = "std")]
+    {
+        DEFAULT_FINGERPRINTER.fingerprint(chain)
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        Fingerprinter::default().fingerprint(chain)
+    }
+}
+
+impl Fingerprinter {
+    /// Creates a new `Fingerprinter` without any normalizers
+    #[must_use]
+    pub fn new() -> Self {
+        Fingerprinter { normalizers: Vec::new() }
+    }
+
+    /// Adds a normalizer, which will run after all previously added normalizers
+    #[must_use]
+    pub fn with_normalizer(mut self, normalizer: impl Normalizer + 'static) -> Self {
+        self.normalizers.push(Box::new(normalizer));
+        self
+    }
+
+    /// Computes the fingerprint of the given error chain
+    #[must_use]
+    pub fn fingerprint(&self, chain: ErrorIterator<'_>) -> Fingerprint {
+        let mut hash = FNV_OFFSET;
+        for err in chain {
+            let formatted;
+            let message = if let Some(err) = err.downcast_ref::<ErrorContext>() {
+                err.message().as_str()
+            } else if let Some(err) = err.downcast_ref::<SerializableError>() {
+                err.context.as_str()
+            } else {
+                formatted = err.to_string();
+                &formatted
+            };
+
+            let mut message = Cow::Borrowed(message);
+            for normalizer in &self.normalizers {
+                let normalized = match normalizer.normalize(&message) {
+                    Cow::Owned(normalized) => normalized,
+                    Cow::Borrowed(_) => continue,
+                };
+                message = Cow::Owned(normalized);
+            }
+
+            // 0xff never appears in UTF-8, making it a safe layer separator
+            for byte in message.bytes().chain([0xff]) {
+                hash ^= u128::from(byte);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+        Fingerprint(hash)
+    }
+}
@@ -0,0 +1,217 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    hash::{BuildHasher, RandomState},
+    sync::{Arc, Mutex, MutexGuard, OnceLock, Weak},
+};
+
+use crate::SharedString;
+
+/// Number of strings a weak interner may hold before dead entries are cleaned up for the first time
+const MIN_PURGE_THRESHOLD: usize = 64;
+
+static GLOBAL_INTERNER: OnceLock<Interner> = OnceLock::new();
+
+enum InternerState {
+    Bounded {
+        strings: HashSet<Arc<str>>,
+        /// insertion order, used to evict the oldest strings first
+        order: VecDeque<Arc<str>>,
+        capacity: usize,
+    },
+    Weak {
+        /// weak references to the strings, grouped by the hash of their contents
+        buckets: HashMap<u64, Vec<Weak<str>>>,
+        hasher: RandomState,
+        /// number of weak references stored, including dead ones
+        stored: usize,
+        purge_threshold: usize,
+    },
+}
+
+/// A thread-safe pool of strings, which makes equal strings share a single `Arc<str>`
+///
+/// Interning repeated dynamic messages saves memory, and makes the pointer equality of the
+/// resulting [`SharedString`]s meaningful.
+///
+/// An interner works in one of two modes:
+/// - [`Interner::bounded`] keeps up to a given number of strings alive, evicting the oldest ones,
+/// - [`Interner::weak`] only keeps track of strings that are still referenced elsewhere.
+///
+/// Once registered with [`set_global_interner`], the interner is also used when decoding
+/// [`SharedString`]s with serde or bincode.
+///
+/// ```
+/// # use cloneable_errors::{Interner, SharedString};
+/// let interner = Interner::weak();
+/// let a = interner.intern_shared(format!("connection refused to {}", "10.0.0.5").into());
+/// let b = interner.intern_shared(format!("connection refused to {}", "10.0.0.5").into());
+/// assert!(matches!(a, SharedString::Arc(_)));
+/// assert_eq!(a, b);
+/// ```
+pub struct Interner {
+    state: Mutex<InternerState>,
+}
+
+impl Interner {
+    /// Creates an interner that keeps at most `capacity` strings alive
+    ///
+    /// When the interner is full, the string that was interned first is forgotten. Strings that
+    /// were already handed out stay valid, but are no longer shared with new ones.
+    #[must_use]
+    pub fn bounded(capacity: usize) -> Self {
+        Interner {
+            state: Mutex::new(InternerState::Bounded {
+                strings: HashSet::new(),
+                order: VecDeque::new(),
+                capacity,
+            }),
+        }
+    }
+
+    /// Creates an interner that only holds weak references to the interned strings
+    ///
+    /// Strings are forgotten once every copy is dropped, so the interner never keeps memory
+    /// alive by itself, apart from the bookkeeping of dead entries, which is cleaned up
+    /// periodically.
+    #[must_use]
+    pub fn weak() -> Self {
+        Interner {
+            state: Mutex::new(InternerState::Weak {
+                buckets: HashMap::new(),
+                hasher: RandomState::new(),
+                stored: 0,
+                purge_threshold: MIN_PURGE_THRESHOLD,
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, InternerState> {
+        self.state.lock().expect("Internal lock got poisoned")
+    }
+
+    /// Returns a shared copy of the given string
+    ///
+    /// If an equal string is already known to the interner, a clone of it is returned.
+    #[must_use]
+    pub fn intern(&self, value: &str) -> Arc<str> {
+        match &mut *self.lock() {
+            InternerState::Bounded {
+                strings,
+                order,
+                capacity,
+            } => {
+                if let Some(string) = strings.get(value) {
+                    return string.clone();
+                }
+                let string: Arc<str> = Arc::from(value);
+                if *capacity == 0 {
+                    return string;
+                }
+                if strings.len() >= *capacity {
+                    if let Some(oldest) = order.pop_front() {
+                        strings.remove(&oldest);
+                    }
+                }
+                strings.insert(string.clone());
+                order.push_back(string.clone());
+                string
+            }
+            InternerState::Weak {
+                buckets,
+                hasher,
+                stored,
+                purge_threshold,
+            } => {
+                let bucket = buckets.entry(hasher.hash_one(value)).or_default();
+                if let Some(string) = bucket
+                    .iter()
+                    .filter_map(Weak::upgrade)
+                    .find(|string| &**string == value)
+                {
+                    return string;
+                }
+                let string: Arc<str> = Arc::from(value);
+                bucket.push(Arc::downgrade(&string));
+                *stored += 1;
+                if *stored >= *purge_threshold {
+                    *stored = purge(buckets);
+                    *purge_threshold = (*stored * 2).max(MIN_PURGE_THRESHOLD);
+                }
+                string
+            }
+        }
+    }
+
+    /// Interns the contents of a [`SharedString::Arc`]
+    ///
+    /// Static and inline strings don't allocate, so they are returned unchanged.
+    #[must_use]
+    pub fn intern_shared(&self, value: SharedString) -> SharedString {
+        match value {
+            SharedString::Arc(string) => SharedString::Arc(self.intern(&string)),
+            other => other,
+        }
+    }
+
+    /// Returns the number of strings currently known to the interner
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match &mut *self.lock() {
+            InternerState::Bounded { strings, .. } => strings.len(),
+            InternerState::Weak { buckets, stored, .. } => {
+                *stored = purge(buckets);
+                *stored
+            }
+        }
+    }
+
+    /// Returns true if the interner does not know any strings
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forgets all interned strings
+    pub fn clear(&self) {
+        match &mut *self.lock() {
+            InternerState::Bounded { strings, order, .. } => {
+                strings.clear();
+                order.clear();
+            }
+            InternerState::Weak { buckets, stored, .. } => {
+                buckets.clear();
+                *stored = 0;
+            }
+        }
+    }
+}
+
+/// Removes dead weak references, returning the number of live ones
+fn purge(buckets: &mut HashMap<u64, Vec<Weak<str>>>) -> usize {
+    buckets.retain(|_, bucket| {
+        bucket.retain(|string| string.strong_count() > 0);
+        !bucket.is_empty()
+    });
+    buckets.values().map(Vec::len).sum()
+}
+
+/// Registers the global [`Interner`], used when decoding [`SharedString`]s
+///
+/// The global interner can only be set once, the interner is given back if it was already set.
+#[allow(clippy::missing_errors_doc)]
+pub fn set_global_interner(interner: Interner) -> Result<(), Interner> {
+    GLOBAL_INTERNER.set(interner)
+}
+
+/// Returns the global [`Interner`], if one was set with [`set_global_interner`]
+#[must_use]
+pub fn global_interner() -> Option<&'static Interner> {
+    GLOBAL_INTERNER.get()
+}
@@ -5,59 +5,125 @@
 * Copyright (C) 2024 mini_bomba
 */
 
-use std::{error::Error, sync::Arc};
+use alloc::{format, string::ToString, sync::Arc, vec::Vec};
+use core::{error::Error, fmt::Display, ptr};
 
 #[cfg(feature = "extensions")]
-use crate::Extension;
-use crate::{ErrorContext, SerializableError, SharedString};
+use crate::{
+    extensions::{copyable_extensions, get_extension, layer_extensions},
+    Extension, ExtensionInstances, MergeExtension,
+};
+use crate::{unwrap::unwrap_error, ChainLinks, ErrorClass, ErrorContext, Fingerprint, Fingerprinter, SerializableError, SharedString};
 
 
 /// `ErrorIterator` - iterates over the chain of [`Error::source`]
 ///
 /// The iterator will attempt to cast away any smart pointers to make downcasting to a concrete
-/// type easier.
+/// type easier. Custom wrapper types can be taught to the iterator using [`crate::register_unwrapper`].
+///
+/// A buggy [`Error::source`] implementation could make the chain infinite. The iterator stops
+/// when it encounters an error it has already yielded, or after yielding
+/// [`ErrorIterator::DEFAULT_MAX_DEPTH`] errors. Use [`ErrorIterator::truncation`] to check
+/// whether that happened.
+#[derive(Clone)]
 pub struct ErrorIterator<'a> {
     next_item: Option<&'a (dyn Error + 'static)>,
+    depth: usize,
+    max_depth: usize,
+    truncation: Option<Truncation>,
+    visited: Visited,
+}
+
+/// The reason why an [`ErrorIterator`] stopped before reaching the end of the chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Truncation {
+    /// The chain loops back to an error that was already yielded
+    Cycle,
+    /// The chain is longer than the maximum depth of the iterator
+    DepthLimit,
+}
+
+impl Display for Truncation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Truncation::Cycle => write!(f, "<cycle detected in the error chain>"),
+            Truncation::DepthLimit => write!(f, "<error chain truncated at the depth limit>"),
+        }
+    }
 }
 
 impl<'a> Iterator for ErrorIterator<'a> {
     type Item = &'a (dyn Error + 'static);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(mut err) = self.next_item {
-            // attempt to cast away any smart pointers
-            // this won't catch every single weird case of wrapping in smart pointers (as it's
-            // impossible) but it catches the most common ones, probably
-            loop {
-                if let Some(downcasted) = err.downcast_ref::<Arc<dyn Error>>() {
-                    err = &**downcasted;
-                    continue;
-                }
-                if let Some(downcasted) = err.downcast_ref::<Arc<dyn Error + Send + Sync>>() {
-                    err = &**downcasted;
-                    continue;
-                }
-                break;
-            }
-
-
-            self.next_item = err.source();
-            Some(err)
-        } else {
-            None
+        let err = unwrap_error(self.next_item.take()?);
+        if self.depth >= self.max_depth {
+            self.truncation = Some(Truncation::DepthLimit);
+            return None;
+        }
+        if !self.visited.insert(err) {
+            self.truncation = Some(Truncation::Cycle);
+            return None;
         }
+        self.depth += 1;
+        self.next_item = err.source();
+        Some(err)
     }
 }
 
 impl<'a> From<&'a (dyn Error + 'static)> for ErrorIterator<'a> {
     fn from(value: &'a (dyn Error + 'static)) -> Self {
-        Self {next_item: Some(value)}
+        Self {
+            next_item: Some(value),
+            depth: 0,
+            max_depth: Self::DEFAULT_MAX_DEPTH,
+            truncation: None,
+            visited: Visited::default(),
+        }
     }
 }
 
-impl ErrorIterator<'_> {
+impl<'a> ErrorIterator<'a> {
+    /// The default maximum number of errors yielded by an iterator
+    pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+    /// Sets the maximum number of errors this iterator will yield
+    #[must_use]
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Returns the reason why the iterator stopped early, if it did
+    #[must_use]
+    pub fn truncation(&self) -> Option<Truncation> {
+        self.truncation
+    }
+
+    /// Collects the remaining errors into [`crate::ChainLink`]s, describing each layer of the stack
+    ///
+    /// ```
+    /// # use std::io;
+    /// # use cloneable_errors::{ErrContext, IntoErrorIterator, LinkKind};
+    /// let error = io::Error::other("disk on fire").context("saving the file");
+    /// let root = error.links().nth_from_root(0).unwrap();
+    /// assert_eq!(root.depth(), 1);
+    /// assert_eq!(root.kind(), LinkKind::Foreign);
+    /// assert_eq!(root.type_name(), Some("std::io::error::Error"));
+    /// ```
+    #[must_use]
+    pub fn links(self) -> ChainLinks<'a> {
+        ChainLinks::new(self)
+    }
+
     /// Copies and flattens the error stack into a [`SerializableError`]
-    /// 
+    ///
+    /// If the iterator stops early (see [`ErrorIterator::truncation`]), a final error describing
+    /// the reason is added to the copy.
+    ///
+    /// With the `extensions` feature enabled, extensions marked as copyable (see
+    /// [`Extension::is_copyable`]) are carried over to the copy.
+    ///
     /// # Panics
     /// Will panic if the iterator is empty.
     /// Pro tip: don't use this on a used iterator.
@@ -69,53 +135,109 @@ impl ErrorIterator<'_> {
         if let Some(err) = first_error.downcast_ref::<SerializableError>() {
             return err.clone()
         }
-        let mut result = SerializableError {
-            context: extract_message(first_error),
-            cause: None,
-        };
+        let mut result = copy_layer(first_error);
         let mut last = &mut result;
 
-        for err in self {
+        for err in self.by_ref() {
             if let Some(err) = err.downcast_ref::<SerializableError>() {
                 last.cause = Some(err.clone().into());
-                break;
+                return result;
             }
-            last.cause = Some(Arc::new(SerializableError { context: extract_message(err), cause: None }));
+            last.cause = Some(Arc::new(copy_layer(err)));
             // should be safe: we've just set this to a new Some(Arc)
             last = Arc::get_mut(last.cause.as_mut().unwrap()).unwrap();
         }
 
+        if let Some(truncation) = self.truncation {
+            last.cause = Some(Arc::new(SerializableError::new(truncation.to_string())));
+        }
+
         result
     }
 
+    /// Classifies the error stack as retryable or permanent
+    ///
+    /// With the `extensions` feature enabled, the nearest [`ErrorClass`] extension is used first.
+    /// Otherwise, with the `std` feature enabled, the first [`std::io::Error`] in the chain with
+    /// a kind recognized by [`ErrorClass::from_io_kind`] decides the class.
+    #[must_use]
+    pub fn classify(self) -> Option<ErrorClass> {
+        #[cfg(feature = "extensions")]
+        if let Some(class) = self.clone().find_extension::<ErrorClass>() {
+            return Some(*class);
+        }
+        #[cfg(feature = "std")]
+        return self
+            .filter_map(|err| err.downcast_ref::<std::io::Error>())
+            .find_map(|err| ErrorClass::from_io_kind(err.kind()));
+        #[cfg(not(feature = "std"))]
+        None
+    }
+
+    /// Estimates the heap memory used by the error stack, in bytes
+    ///
+    /// Every error in the chain is assumed to be stored in its own [`Arc`]. Shared data (like
+    /// messages or extensions shared with other errors) is counted in full.
+    #[must_use]
+    pub fn estimated_size(self) -> usize {
+        self.map(estimate_layer_size).sum()
+    }
+
     /// Retrieves the most recent instance of a given extension type from the error stack
     #[cfg(feature = "extensions")]
     #[must_use]
-    #[allow(clippy::missing_panics_doc)] // the only panic path would be a bug
     pub fn find_extension<E: Extension>(self) -> Option<Arc<E>> {
-        for err in self {
-            use std::any::TypeId;
-            use crate::MaskExtension;
-
-            let Some(err) = err.downcast_ref::<ErrorContext>() else { continue };
-
-            if let Some(ext) = err.extensions.as_ref()
-                .and_then(|m| 
-                    m.get(&TypeId::of::<E>()).cloned()
-                )
-            {
-                return Some(Arc::downcast(ext).expect("BUG: Extension stored under the wrong TypeId!"))
-            }
-            if err.extensions.as_ref()
-                .is_some_and(|m|
-                    m.contains_key(&TypeId::of::<MaskExtension<E>>())
-                )
-            {
-                // found a mask matching the requested extension
-                return None
-            }
-        }
-        None
+        self.find_all_extensions().next()
+    }
+
+    /// Retrieves every instance of a given extension type from the error stack, starting from the
+    /// most recent one
+    ///
+    /// Instances hidden by [`ErrorContext::remove_extension`] are not included.
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use cloneable_errors::{anyhow, ErrContext, Extension, IntoErrorIterator};
+    /// struct Tag(&'static str);
+    /// impl Extension for Tag {}
+    ///
+    /// let error = anyhow!("inner", extend: Arc::new(Tag("db")))
+    ///     .context("outer")
+    ///     .with_extension(Arc::new(Tag("api")));
+    /// let tags: Vec<_> = error.find_all_extensions::<Tag>().map(|tag| tag.0).collect();
+    /// assert_eq!(tags, ["api", "db"]);
+    /// ```
+    #[cfg(feature = "extensions")]
+    #[must_use]
+    pub fn find_all_extensions<E: Extension>(self) -> ExtensionInstances<'a, E> {
+        ExtensionInstances::new(self)
+    }
+
+    /// Retrieves the deepest visible instance of a given extension type from the error stack
+    #[cfg(feature = "extensions")]
+    #[must_use]
+    pub fn find_root_extension<E: Extension>(self) -> Option<Arc<E>> {
+        self.find_all_extensions().last()
+    }
+
+    /// Combines every visible instance of a given extension type in the error stack into one,
+    /// using [`MergeExtension::merge`]
+    ///
+    /// Instances are merged starting from the deepest one. If there is only one instance, it's
+    /// returned as is.
+    #[cfg(feature = "extensions")]
+    #[must_use]
+    pub fn merged_extension<E: MergeExtension>(self) -> Option<Arc<E>> {
+        let mut instances: Vec<_> = self.find_all_extensions::<E>().collect();
+        let deepest = instances.pop()?;
+        Some(instances.into_iter().rev().fold(deepest, |deeper, ext| Arc::new(ext.merge(&deeper))))
+    }
+
+    /// Retrieves an instance of a given extension type attached to the next error, without looking
+    /// at deeper layers
+    #[cfg(feature = "extensions")]
+    #[must_use]
+    pub fn get_extension<E: Extension>(mut self) -> Option<Arc<E>> {
+        self.next().and_then(layer_extensions).and_then(get_extension)
     }
 }
 
@@ -124,38 +246,181 @@ pub trait IntoErrorIterator {
     #[must_use]
     fn error_chain(&self) -> ErrorIterator<'_>;
 
+    /// Collects the error stack into [`crate::ChainLink`]s, describing each layer of the stack
+    ///
+    /// See [`ErrorIterator::links`] for details.
+    #[must_use]
+    fn links(&self) -> ChainLinks<'_> {
+        self.error_chain().links()
+    }
+
     /// Copies and flattens the error stack into a [`SerializableError`]
     #[must_use]
     fn serializable_copy(&self) -> SerializableError {
         self.error_chain().serializable_copy()
     }
 
+    /// Classifies the error stack as retryable or permanent
+    ///
+    /// See [`ErrorIterator::classify`] for details.
+    #[must_use]
+    fn classify(&self) -> Option<ErrorClass> {
+        self.error_chain().classify()
+    }
+
+    /// Estimates the heap memory used by the error stack, in bytes
+    ///
+    /// See [`ErrorIterator::estimated_size`] for details.
+    #[must_use]
+    fn estimated_size(&self) -> usize {
+        self.error_chain().estimated_size()
+    }
+
+    /// Computes a stable [`Fingerprint`] of the error stack, using the default normalizers
+    ///
+    /// Only the messages of the errors are hashed, not their type names, so that serializable
+    /// copies share the fingerprint of the original error. See [`Fingerprinter`] for details.
+    #[must_use]
+    fn fingerprint(&self) -> Fingerprint {
+        crate::fingerprint::default_fingerprint(self.error_chain())
+    }
+
+    /// Computes a stable [`Fingerprint`] of the error stack, using the given [`Fingerprinter`]
+    #[must_use]
+    fn fingerprint_with(&self, fingerprinter: &Fingerprinter) -> Fingerprint {
+        fingerprinter.fingerprint(self.error_chain())
+    }
+
     /// Retrieves the most recent instance of a given extension type from the error stack
     #[cfg(feature = "extensions")]
     #[must_use]
     fn find_extension<E: Extension>(&self) -> Option<Arc<E>> {
         self.error_chain().find_extension()
     }
+
+    /// Retrieves every instance of a given extension type from the error stack
+    ///
+    /// See [`ErrorIterator::find_all_extensions`] for details.
+    #[cfg(feature = "extensions")]
+    #[must_use]
+    fn find_all_extensions<E: Extension>(&self) -> ExtensionInstances<'_, E> {
+        self.error_chain().find_all_extensions()
+    }
+
+    /// Retrieves the deepest visible instance of a given extension type from the error stack
+    #[cfg(feature = "extensions")]
+    #[must_use]
+    fn find_root_extension<E: Extension>(&self) -> Option<Arc<E>> {
+        self.error_chain().find_root_extension()
+    }
+
+    /// Combines every visible instance of a given extension type in the error stack into one
+    ///
+    /// See [`ErrorIterator::merged_extension`] for details.
+    #[cfg(feature = "extensions")]
+    #[must_use]
+    fn merged_extension<E: MergeExtension>(&self) -> Option<Arc<E>> {
+        self.error_chain().merged_extension()
+    }
+
+    /// Retrieves an instance of a given extension type attached to the top layer of the error
+    /// stack, without looking at its causes
+    #[cfg(feature = "extensions")]
+    #[must_use]
+    fn get_extension<E: Extension>(&self) -> Option<Arc<E>> {
+        self.error_chain().get_extension()
+    }
 }
 
 impl<T> IntoErrorIterator for T
 where T: Error + 'static
 {
     fn error_chain(&self) -> ErrorIterator<'_> {
-        ErrorIterator { next_item: Some(self) }
+        ErrorIterator::from(self as &(dyn Error + 'static))
     }
 }
 
 /// Extracts the top-level error message into a [`SharedString`] with optimizations for types defined in this crate
-fn extract_message(err: &(dyn Error + 'static)) -> SharedString {
+pub(crate) fn extract_message(err: &(dyn Error + 'static)) -> SharedString {
     if let Some(err) = err.downcast_ref::<SerializableError>() {
         // clone the context
         err.context.clone()
     } else if let Some(err) = err.downcast_ref::<ErrorContext>() {
         // clone the context
-        err.context.clone()
+        err.message().clone()
     } else {
         // not our type, format
         format!("{err}").into()
     }
 }
+
+/// Copies the message (and copyable extensions) of a single error into a [`SerializableError`]
+fn copy_layer(err: &(dyn Error + 'static)) -> SerializableError {
+    SerializableError {
+        context: extract_message(err),
+        cause: None,
+        #[cfg(feature = "extensions")]
+        extensions: copyable_extensions(err),
+    }
+}
+
+/// Size of the reference counts stored in every [`Arc`] allocation
+pub(crate) const ARC_OVERHEAD: usize = 2 * size_of::<usize>();
+
+/// Estimates the heap memory used by a single error, assuming it is stored in an [`Arc`]
+fn estimate_layer_size(err: &(dyn Error + 'static)) -> usize {
+    fn message_size(msg: &SharedString) -> usize {
+        match msg {
+            SharedString::Arc(s) => ARC_OVERHEAD + s.len(),
+            SharedString::Static(_) | SharedString::Inline(_) => 0,
+        }
+    }
+
+    #[allow(unused_mut)]
+    let mut size = if let Some(err) = err.downcast_ref::<SerializableError>() {
+        ARC_OVERHEAD + size_of::<SerializableError>() + message_size(&err.context)
+    } else if let Some(err) = err.downcast_ref::<ErrorContext>() {
+        ErrorContext::ALLOCATION_SIZE + message_size(err.message())
+    } else {
+        ARC_OVERHEAD + size_of_val(err)
+    };
+    #[cfg(feature = "extensions")]
+    if let Some(extensions) = layer_extensions(err) {
+        size += extensions.estimated_size();
+    }
+    size
+}
+
+/// The set of errors already yielded by an [`ErrorIterator`]
+///
+/// Errors are compared by address and vtable, so an error is not mistaken for another one
+/// stored at the start of it. The compiler may create multiple vtables for the same type, in
+/// which case a cycle is only detected after it's traversed for the second time.
+#[derive(Clone, Default)]
+struct Visited {
+    inline: [Option<*const (dyn Error + 'static)>; 8],
+    spilled: Vec<*const (dyn Error + 'static)>,
+}
+
+impl Visited {
+    /// Adds the error to the set, returning false if it was already present
+    #[allow(ambiguous_wide_pointer_comparisons)] // intended, see above
+    fn insert(&mut self, err: &(dyn Error + 'static)) -> bool {
+        let err = ptr::from_ref(err);
+        for slot in &mut self.inline {
+            match slot {
+                Some(visited) if ptr::eq(*visited, err) => return false,
+                Some(_) => {}
+                None => {
+                    *slot = Some(err);
+                    return true;
+                }
+            }
+        }
+        if self.spilled.iter().any(|visited| ptr::eq(*visited, err)) {
+            return false;
+        }
+        self.spilled.push(err);
+        true
+    }
+}
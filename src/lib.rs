@@ -7,21 +7,96 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![cfg_attr(docsrs, feature(doc_auto_cfg))]
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
+mod breaker;
+#[cfg(feature = "std")]
+mod cache;
+mod classify;
+mod content;
 mod context;
+mod error;
+#[cfg(feature = "extensions")]
+mod extension_codec;
+#[cfg(feature = "extensions")]
+mod extension_map;
 #[cfg(feature = "extensions")]
 mod extensions;
+mod fingerprint;
+#[cfg(feature = "std")]
+mod interner;
 mod iterator;
+mod links;
+#[cfg(feature = "std")]
+mod retry;
 mod serializable;
+#[cfg(feature = "futures")]
+mod single_flight;
 mod strings;
+mod unwrap;
 mod util;
 
+#[cfg(feature = "std")]
+pub use breaker::*;
+#[cfg(feature = "std")]
+pub use cache::*;
+pub use classify::*;
+pub use content::*;
 pub use context::*;
+pub use error::Error;
+#[cfg(feature = "extensions")]
+pub use extension_codec::*;
+#[cfg(feature = "extensions")]
+pub use extension_map::ExtensionMap;
 #[cfg(feature = "extensions")]
 pub use extensions::*;
+pub use fingerprint::*;
+#[cfg(feature = "std")]
+pub use interner::{global_interner, set_global_interner, Interner};
 pub use iterator::*;
+pub use links::*;
+#[cfg(feature = "std")]
+pub use retry::*;
 pub use serializable::*;
+#[cfg(feature = "futures")]
+pub use single_flight::*;
 pub use strings::*;
+pub use unwrap::*;
+
+/// Derives the [`Extension`] trait, optionally rendering the extension in [`Debug`] output or
+/// implementing [`MergeExtension`]
+///
+/// ```
+/// # use std::{collections::BTreeSet, sync::Arc};
+/// # use cloneable_errors::{anyhow, ErrContext, Extension, IntoErrorIterator};
+/// #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Extension)]
+/// #[extension(debug, merge = "max")]
+/// enum Severity {
+///     Warning,
+///     Fatal,
+/// }
+///
+/// #[derive(Clone, Debug, Extension)]
+/// #[extension(tag = "tags", merge = "union")]
+/// struct Tags(BTreeSet<&'static str>);
+///
+/// let error = anyhow!("disk full", extend: Arc::new(Severity::Fatal), Arc::new(Tags(["io"].into())))
+///     .context("saving the file")
+///     .with_extension(Arc::new(Severity::Warning))
+///     .with_extension(Arc::new(Tags(["api"].into())));
+///
+/// assert_eq!(*error.merged_extension::<Severity>().unwrap(), Severity::Fatal);
+/// assert_eq!(error.merged_extension::<Tags>().unwrap().0, ["api", "io"].into());
+/// assert_eq!(format!("{error:?}"), "saving the file\n    - Warning\n\nCaused by:\n    0: disk full\n       - Fatal");
+/// ```
+///
+/// See the documentation of the macro in the `cloneable_errors_derive` crate for all supported
+/// attributes.
+#[cfg(feature = "derive")]
+pub use cloneable_errors_derive::Extension;
 
 #[macro_export]
 /// Create a new [`ErrorContext`] stack
@@ -108,20 +183,24 @@ macro_rules! anyhow {
         $crate::ErrorContext::new($val)
     };
     (($($format:tt)+), extend: $($ext:expr),+) => {
-        $crate::ErrorContext::new(format!($($format)+))$(.with_extension($ext))+
+        $crate::ErrorContext::new($crate::shared_format!($($format)+))$(.with_extension($ext))+
     };
     ($val:expr, extend: $($ext:expr),+) => {
         $crate::ErrorContext::new($val)$(.with_extension($ext))+
     };
     ($($tok:tt)+) => {
-        $crate::ErrorContext::new(format!($($tok)+))
+        $crate::ErrorContext::new($crate::shared_format!($($tok)+))
     };
 }
 
 #[macro_export]
 /// Create a new [`ErrorContext`] stack using [`anyhow!`] and immediately return it as [`Result::Err`]
+///
+/// The error is converted using [`From`], so this can be used in functions returning either
+/// [`ErrorContext`] or [`Error`] errors. In closures and async blocks, the error type has to be
+/// known from elsewhere, e.g. from a return type annotation.
 macro_rules! bail {
     ($($tok:tt)+) => {
-        return Err($crate::anyhow!($($tok)+));
+        return Err(::core::convert::From::from($crate::anyhow!($($tok)+)));
     };
 }
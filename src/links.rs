@@ -0,0 +1,192 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "extensions")]
+use alloc::sync::Arc;
+#[cfg(feature = "extensions")]
+use core::any::TypeId;
+use core::{any::type_name, error::Error, iter::FusedIterator};
+
+#[cfg(feature = "extensions")]
+use crate::extensions::{get_extension, layer_extensions, visible_extensions, Extension};
+#[cfg(feature = "extensions")]
+use crate::ExtensionMap;
+use crate::{ErrorContext, ErrorIterator, SerializableError, Truncation};
+
+/// The kind of error found at a given [`ChainLink`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LinkKind {
+    /// An [`ErrorContext`] layer
+    Context,
+    /// A [`SerializableError`] layer
+    Serializable,
+    /// Any other error type
+    Foreign,
+}
+
+/// A single layer of an error stack, with metadata about it
+///
+/// Created by [`ErrorIterator::links`].
+#[derive(Clone, Copy, Debug)]
+pub struct ChainLink<'a> {
+    depth: usize,
+    error: &'a (dyn Error + 'static),
+    type_name: Option<&'static str>,
+    kind: LinkKind,
+}
+
+impl<'a> ChainLink<'a> {
+    fn new(depth: usize, error: &'a (dyn Error + 'static), captured_type_name: Option<&'static str>) -> Self {
+        let (kind, type_name) = if error.is::<ErrorContext>() {
+            (LinkKind::Context, Some(type_name::<ErrorContext>()))
+        } else if error.is::<SerializableError>() {
+            (LinkKind::Serializable, Some(type_name::<SerializableError>()))
+        } else {
+            (LinkKind::Foreign, captured_type_name)
+        };
+        ChainLink { depth, error, type_name, kind }
+    }
+
+    /// The position of this layer in the stack, with 0 being the outermost error
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The error at this layer
+    #[must_use]
+    pub fn error(&self) -> &'a (dyn Error + 'static) {
+        self.error
+    }
+
+    /// The [`std::any::type_name`] of the error at this layer, if known
+    ///
+    /// Always known for types defined in this crate. For other types, the name is only known if
+    /// the error was wrapped using [`crate::ErrContext::context`], in which case it's the name of
+    /// the type passed to that function.
+    #[must_use]
+    pub fn type_name(&self) -> Option<&'static str> {
+        self.type_name
+    }
+
+    /// The kind of error at this layer
+    #[must_use]
+    pub fn kind(&self) -> LinkKind {
+        self.kind
+    }
+
+    /// Returns the extensions attached directly to this layer
+    ///
+    /// Only [`ErrorContext`] and [`SerializableError`] layers can have extensions.
+    #[cfg(feature = "extensions")]
+    #[must_use]
+    pub fn extensions(&self) -> LayerExtensions<'a> {
+        LayerExtensions {
+            map: layer_extensions(self.error),
+        }
+    }
+}
+
+/// A view of the extensions attached to a single layer of an error stack
+///
+/// Unlike [`ErrorIterator::find_extension`], this does not look at deeper layers.
+#[cfg(feature = "extensions")]
+#[derive(Clone, Copy)]
+pub struct LayerExtensions<'a> {
+    map: Option<&'a ExtensionMap>,
+}
+
+#[cfg(feature = "extensions")]
+impl<'a> LayerExtensions<'a> {
+    /// Retrieves the extension of a given type, if attached to this layer
+    #[must_use]
+    pub fn get<E: Extension>(&self) -> Option<Arc<E>> {
+        self.map.and_then(get_extension)
+    }
+
+    /// Returns true if an extension of a given type is attached to this layer
+    #[must_use]
+    pub fn contains<E: Extension>(&self) -> bool {
+        self.map.is_some_and(|m| m.contains_key(&TypeId::of::<E>()))
+    }
+
+    /// Iterates over the extensions attached to this layer, in no particular order
+    ///
+    /// See [`ErrorContext::extensions`].
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &'a dyn Extension)> + use<'a> {
+        visible_extensions(self.map)
+    }
+
+    /// Returns true if this layer hides extensions of a given type attached to deeper layers
+    #[must_use]
+    pub fn masks<E: Extension>(&self) -> bool {
+        self.map.is_some_and(|m| m.is_masked(&TypeId::of::<E>()))
+    }
+}
+
+/// An iterator over the [`ChainLink`]s of an error stack
+///
+/// The whole chain is walked when this iterator is created, which allows iterating it in reverse
+/// (starting from the root cause).
+#[derive(Clone, Debug)]
+pub struct ChainLinks<'a> {
+    links: vec::IntoIter<ChainLink<'a>>,
+    truncation: Option<Truncation>,
+}
+
+impl<'a> ChainLinks<'a> {
+    pub(crate) fn new(mut iter: ErrorIterator<'a>) -> Self {
+        let mut links = Vec::new();
+        let mut captured_type_name = None;
+        for (depth, error) in iter.by_ref().enumerate() {
+            links.push(ChainLink::new(depth, error, captured_type_name));
+            captured_type_name = error.downcast_ref::<ErrorContext>().and_then(ErrorContext::cause_type_name);
+        }
+        ChainLinks {
+            links: links.into_iter(),
+            truncation: iter.truncation(),
+        }
+    }
+
+    /// Returns the reason why the chain was cut short, if it was
+    ///
+    /// See [`ErrorIterator::truncation`].
+    #[must_use]
+    pub fn truncation(&self) -> Option<Truncation> {
+        self.truncation
+    }
+
+    /// Returns the `n`th remaining link, counting from the root cause
+    ///
+    /// Like [`DoubleEndedIterator::nth_back`], this consumes the links up to and including the
+    /// returned one from the back of the iterator.
+    pub fn nth_from_root(&mut self, n: usize) -> Option<ChainLink<'a>> {
+        self.links.nth_back(n)
+    }
+}
+
+impl<'a> Iterator for ChainLinks<'a> {
+    type Item = ChainLink<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.links.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.links.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for ChainLinks<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.links.next_back()
+    }
+}
+
+impl ExactSizeIterator for ChainLinks<'_> {}
+impl FusedIterator for ChainLinks<'_> {}
@@ -0,0 +1,216 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use std::{future::Future, sync::Arc, time::Duration};
+
+use crate::{ErrorClass, ErrorContext, IntoErrorIterator};
+#[cfg(feature = "extensions")]
+use crate::Extension;
+
+/// An extension hinting how long to wait before retrying the failed operation
+///
+/// When found on an error, [`RetryPolicy`] waits for this duration (capped at the maximum delay)
+/// instead of the regular backoff delay.
+#[cfg(feature = "extensions")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryAfter(pub Duration);
+
+#[cfg(feature = "extensions")]
+impl Extension for RetryAfter {}
+
+/// An extension attached to errors returned by [`RetryPolicy`] after more than one attempt
+#[cfg(feature = "extensions")]
+#[derive(Clone, Debug)]
+pub struct RetryHistory {
+    /// Number of attempts made
+    pub attempts: u32,
+    /// Errors returned by each attempt, in order
+    pub errors: Vec<ErrorContext>,
+}
+
+#[cfg(feature = "extensions")]
+impl Extension for RetryHistory {}
+
+/// Retries fallible operations with exponential backoff, based on the classification of errors
+///
+/// After each failed attempt, the error is classified using a classifier function, which
+/// defaults to [`IntoErrorIterator::classify`]. Only [`ErrorClass::Retryable`] errors are
+/// retried, unless [`RetryPolicy::retry_unclassified`] is enabled.
+///
+/// If the operation failed more than once, the returned error is a new [`ErrorContext`] layer
+/// caused by the last error. With the `extensions` feature enabled, this layer carries a
+/// [`RetryHistory`] extension holding every error, and [`RetryAfter`] hints are respected.
+/// ```
+/// # use std::{io, time::Duration};
+/// # use cloneable_errors::{ErrContext, IntoErrorIterator, RetryPolicy};
+/// let policy = RetryPolicy::new(3).with_delay(Duration::from_millis(1));
+/// let error = policy
+///     .retry(|attempt| Err::<(), _>(io::Error::from(io::ErrorKind::TimedOut).context(format!("attempt {attempt} failed"))))
+///     .unwrap_err();
+///
+/// assert_eq!(format!("{error}"), "operation failed after 3 attempts");
+/// assert_eq!(format!("{}", error.error_chain().nth(1).unwrap()), "attempt 3 failed");
+/// # #[cfg(feature = "extensions")] {
+/// let history = error.find_extension::<cloneable_errors::RetryHistory>().unwrap();
+/// assert_eq!(history.attempts, 3);
+/// assert_eq!(format!("{}", history.errors[0]), "attempt 1 failed");
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+    multiplier: u32,
+    retry_unclassified: bool,
+    classifier: fn(&ErrorContext) -> Option<ErrorClass>,
+}
+
+impl Default for RetryPolicy {
+    /// 3 attempts, with delays starting at 100ms and doubling each time, up to 10s
+    fn default() -> Self {
+        RetryPolicy::new(3)
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a new retry policy, making at most `max_attempts` attempts
+    ///
+    /// Delays start at 100ms and double after each attempt, up to 10s.
+    #[must_use]
+    pub fn new(max_attempts: u32) -> Self {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            multiplier: 2,
+            retry_unclassified: false,
+            classifier: |err| err.classify(),
+        }
+    }
+
+    /// Sets the delay before the second attempt
+    #[must_use]
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Sets the maximum delay between attempts, including delays requested by [`RetryAfter`]
+    #[must_use]
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Sets the number the delay gets multiplied by after each attempt
+    ///
+    /// Use 1 for a constant delay.
+    #[must_use]
+    pub fn with_multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Sets whether errors that couldn't be classified should be retried
+    ///
+    /// Disabled by default.
+    #[must_use]
+    pub fn retry_unclassified(mut self, retry: bool) -> Self {
+        self.retry_unclassified = retry;
+        self
+    }
+
+    /// Replaces the function used to classify errors
+    #[must_use]
+    pub fn with_classifier(mut self, classifier: fn(&ErrorContext) -> Option<ErrorClass>) -> Self {
+        self.classifier = classifier;
+        self
+    }
+
+    /// Runs the operation until it succeeds, fails with a non-retryable error, or the maximum
+    /// number of attempts is reached, sleeping the current thread between attempts.
+    ///
+    /// The closure receives the number of the current attempt, starting from 1.
+    #[allow(clippy::missing_errors_doc)]
+    pub fn retry<T, F>(&self, mut op: F) -> Result<T, ErrorContext>
+    where
+        F: FnMut(u32) -> Result<T, ErrorContext>,
+    {
+        let mut history = Vec::new();
+        loop {
+            let attempt = u32::try_from(history.len()).unwrap_or(u32::MAX).saturating_add(1);
+            match op(attempt) {
+                Ok(value) => return Ok(value),
+                Err(err) => std::thread::sleep(self.next_delay(&mut history, err)?),
+            }
+        }
+    }
+
+    /// Runs the future returned by the operation until it succeeds, fails with a non-retryable
+    /// error, or the maximum number of attempts is reached.
+    ///
+    /// The closure receives the number of the current attempt, starting from 1. The `sleep`
+    /// function is used to wait between attempts, which allows this function to work with any
+    /// async runtime (for example, pass `tokio::time::sleep`).
+    #[allow(clippy::missing_errors_doc)]
+    pub async fn retry_async<T, F, Fut, S, SFut>(&self, mut sleep: S, mut op: F) -> Result<T, ErrorContext>
+    where
+        F: FnMut(u32) -> Fut,
+        Fut: Future<Output = Result<T, ErrorContext>>,
+        S: FnMut(Duration) -> SFut,
+        SFut: Future<Output = ()>,
+    {
+        let mut history = Vec::new();
+        loop {
+            let attempt = u32::try_from(history.len()).unwrap_or(u32::MAX).saturating_add(1);
+            match op(attempt).await {
+                Ok(value) => return Ok(value),
+                Err(err) => sleep(self.next_delay(&mut history, err)?).await,
+            }
+        }
+    }
+
+    /// Records a failed attempt, returning the time to wait before the next attempt, or the final
+    /// error if the operation should not be retried.
+    fn next_delay(&self, history: &mut Vec<ErrorContext>, err: ErrorContext) -> Result<Duration, ErrorContext> {
+        let retryable = (self.classifier)(&err).map_or(self.retry_unclassified, ErrorClass::is_retryable);
+        #[cfg(feature = "extensions")]
+        let hint = err.find_extension::<RetryAfter>();
+        history.push(err);
+        let attempts = u32::try_from(history.len()).unwrap_or(u32::MAX);
+
+        if !retryable || attempts >= self.max_attempts {
+            return Err(Self::final_error(std::mem::take(history)));
+        }
+
+        let backoff = self
+            .multiplier
+            .checked_pow(attempts - 1)
+            .and_then(|factor| self.initial_delay.checked_mul(factor))
+            .unwrap_or(Duration::MAX);
+        #[cfg(feature = "extensions")]
+        let backoff = hint.map_or(backoff, |hint| hint.0);
+        Ok(backoff.min(self.max_delay))
+    }
+
+    fn final_error(mut errors: Vec<ErrorContext>) -> ErrorContext {
+        if errors.len() == 1 {
+            return errors.pop().expect("length was checked");
+        }
+        let attempts = u32::try_from(errors.len()).unwrap_or(u32::MAX);
+        let last = errors.last().expect("length was checked").clone();
+        let error = ErrorContext::from_parts(
+            format!("operation failed after {attempts} attempts").into(),
+            Some(Arc::new(last)),
+            None,
+        );
+        #[cfg(feature = "extensions")]
+        let error = error.with_extension(Arc::new(RetryHistory { attempts, errors }));
+        error
+    }
+}
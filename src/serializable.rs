@@ -5,42 +5,168 @@
 * Copyright (C) 2024-2025 mini_bomba
 */
 
-use std::{error::Error, fmt::{Display, Debug}, sync::Arc};
+#[cfg(any(feature = "serde", feature = "bincode"))]
+use alloc::{string::String, vec::Vec};
+use alloc::sync::Arc;
+use core::{error::Error, fmt::{Display, Debug}, hash::{Hash, Hasher}};
 
 #[cfg(feature="serde")]
 use serde::{Deserialize, Serialize};
 
-#[cfg(feature="bincode")]
-use bincode::{Decode, Encode};
-
+#[cfg(feature = "extensions")]
+use crate::extensions::{
+    insert_extension, remove_extension, visible_extensions, write_extensions, Extension,
+};
+#[cfg(feature = "extensions")]
+use crate::ExtensionMap;
 use crate::{IntoErrorIterator, SharedString};
 
 
 /// An error stack with all messages flattened into strings, trivial to (de)serialize
+///
+/// With the `extensions` feature enabled, extensions can be attached to a `SerializableError`
+/// just like to an [`crate::ErrorContext`]. Only extensions implementing
+/// [`crate::SerializableExtension`] and registered with `register_extension` are serialized,
+/// other extensions are dropped.
 #[derive(Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[cfg_attr(feature = "bincode", derive(Encode, Decode))]
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(from = "SerializedError", into = "SerializedError"))]
 pub struct SerializableError {
     pub context: SharedString,
     pub cause: Option<Arc<SerializableError>>,
+    #[cfg(feature = "extensions")]
+    pub(crate) extensions: Option<ExtensionMap>,
+}
+
+/// An extension or a mask in its serialized form: the tag of the extension, followed by the
+/// encoded extension, or [`None`] for a mask
+#[cfg(any(feature = "serde", feature = "bincode"))]
+pub(crate) type EncodedExtension = (String, Option<String>);
+
+#[cfg(any(feature = "serde", feature = "bincode"))]
+impl SerializableError {
+    /// Creates an error from its serialized parts, decoding the registered extensions
+    #[cfg(feature = "extensions")]
+    fn from_encoded(
+        context: SharedString,
+        cause: Option<Arc<SerializableError>>,
+        extensions: Vec<EncodedExtension>,
+    ) -> Self {
+        SerializableError {
+            context,
+            cause,
+            extensions: crate::extension_codec::decode_extensions(extensions),
+        }
+    }
+
+    /// Creates an error from its serialized parts, dropping the extensions
+    #[cfg(not(feature = "extensions"))]
+    fn from_encoded(context: SharedString, cause: Option<Arc<SerializableError>>, _: Vec<EncodedExtension>) -> Self {
+        SerializableError { context, cause }
+    }
+
+    /// Encodes the registered extensions attached directly to the error
+    #[cfg(feature = "extensions")]
+    fn encoded_extensions(error: &Self) -> Vec<EncodedExtension> {
+        crate::extension_codec::encode_extensions(error.extensions.as_ref())
+    }
+
+    /// Encodes the extensions of the error, of which there are none without the `extensions`
+    /// feature
+    #[cfg(not(feature = "extensions"))]
+    fn encoded_extensions(_: &Self) -> Vec<EncodedExtension> {
+        Vec::new()
+    }
+}
+
+impl SerializableError {
+    /// Creates a new `SerializableError` with the given message and no cause
+    #[must_use]
+    pub fn new<T>(msg: T) -> SerializableError
+    where
+        T: Into<SharedString>,
+    {
+        SerializableError {
+            context: msg.into(),
+            cause: None,
+            #[cfg(feature = "extensions")]
+            extensions: None,
+        }
+    }
+}
+
+#[cfg(feature = "extensions")]
+impl SerializableError {
+    /// Returns the extensions attached directly to this error, if any
+    #[must_use]
+    pub fn extension_map(&self) -> Option<&ExtensionMap> {
+        self.extensions.as_ref()
+    }
+
+    /// Adds an extension to the error.
+    ///
+    /// See [`crate::ErrorContext::with_extension`].
+    #[must_use]
+    pub fn with_extension(mut self, ext: Arc<dyn Extension>) -> Self {
+        self.add_extension(ext);
+        self
+    }
+
+    /// Adds an extension to the error.
+    ///
+    /// See [`crate::ErrorContext::add_extension`].
+    pub fn add_extension(&mut self, ext: Arc<dyn Extension>) {
+        insert_extension(&mut self.extensions, ext);
+    }
+
+    /// Removes an extension of a given type from the error, if it exists.
+    ///
+    /// See [`crate::ErrorContext::without_extension`].
+    #[must_use]
+    pub fn without_extension<E: Extension>(mut self) -> Self {
+        self.remove_extension::<E>();
+        self
+    }
+
+    /// Removes an extension of a given type from the error, if it exists, returning the removed
+    /// extension.
+    ///
+    /// See [`crate::ErrorContext::remove_extension`].
+    pub fn remove_extension<E: Extension>(&mut self) -> Option<Arc<E>> {
+        let cause = self.cause.as_deref().map(|cause| cause as &(dyn Error + 'static));
+        remove_extension(&mut self.extensions, cause)
+    }
+
+    /// Iterates over the extensions attached directly to this error, in no particular order
+    ///
+    /// See [`crate::ErrorContext::extensions`].
+    pub fn extensions(&self) -> impl Iterator<Item = (&'static str, &dyn Extension)> {
+        visible_extensions(self.extensions.as_ref())
+    }
 }
 
 impl Display for SerializableError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.context)
     }
 }
 
 impl Debug for SerializableError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{self}")?;
+        #[cfg(feature = "extensions")]
+        write_extensions(f, self, "    ")?;
 
         let mut iter = self.error_chain().skip(1).enumerate();
         if let Some((i, item)) = iter.next() {
             write!(f, "\n\nCaused by:\n    {i}: {item}")?;
+            #[cfg(feature = "extensions")]
+            write_extensions(f, item, "       ")?;
 
             for (i, item) in iter {
                 write!(f, "\n    {i}: {item}")?;
+                #[cfg(feature = "extensions")]
+                write_extensions(f, item, "       ")?;
             }
         }
 
@@ -59,7 +185,7 @@ impl Error for SerializableError {
 }
 
 /// Unlike [`crate::ErrorContext`] and [`SharedString`], [`SerializableError`]s are compared by
-/// comparing each string value in the chain.
+/// comparing each string value in the chain. Extensions are not compared.
 /// Therefore, two instances of [`SerializableError`] deserialized from the same data will be equal
 /// to each other.
 impl PartialEq for SerializableError {
@@ -70,6 +196,14 @@ impl PartialEq for SerializableError {
 
 impl Eq for SerializableError {}
 
+/// Consistent with the [`PartialEq`] impl, every string value in the chain is hashed.
+impl Hash for SerializableError {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.context.as_str().hash(state);
+        self.cause.hash(state);
+    }
+}
+
 #[cfg(feature = "anyhow")]
 impl SerializableError {
     /// Convert an [`anyhow::Error`] into a [`SerializableError`]
@@ -79,3 +213,74 @@ impl SerializableError {
         crate::ErrorIterator::from(&**err as &(dyn Error + 'static)).serializable_copy()
     }
 }
+
+#[cfg(feature = "eyre")]
+impl SerializableError {
+    /// Convert an [`eyre::Report`] into a [`SerializableError`]
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)] // should never panic
+    pub fn from_eyre(err: &eyre::Report) -> Self {
+        crate::ErrorIterator::from(&**err as &(dyn Error + 'static)).serializable_copy()
+    }
+}
+
+// serde
+
+/// The serde representation of a [`SerializableError`]
+///
+/// The list of extensions is always present, to keep the format independent of enabled features.
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct SerializedError {
+    context: SharedString,
+    cause: Option<Arc<SerializableError>>,
+    #[serde(default)]
+    extensions: Vec<EncodedExtension>,
+}
+
+#[cfg(feature = "serde")]
+impl From<SerializableError> for SerializedError {
+    fn from(value: SerializableError) -> Self {
+        SerializedError {
+            extensions: SerializableError::encoded_extensions(&value),
+            context: value.context,
+            cause: value.cause,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<SerializedError> for SerializableError {
+    fn from(value: SerializedError) -> Self {
+        SerializableError::from_encoded(value.context, value.cause, value.extensions)
+    }
+}
+
+// bincode
+
+#[cfg(feature = "bincode")]
+mod bincode_impl {
+    use alloc::{sync::Arc, vec::Vec};
+
+    use bincode::{impl_borrow_decode, Decode, Encode};
+    use super::{EncodedExtension, SerializableError};
+
+    impl Encode for SerializableError {
+        fn encode<E: bincode::enc::Encoder>(&self, encoder: &mut E) -> Result<(), bincode::error::EncodeError> {
+            self.context.encode(encoder)?;
+            self.cause.encode(encoder)?;
+            SerializableError::encoded_extensions(self).encode(encoder)
+        }
+    }
+
+    impl<Context> Decode<Context> for SerializableError {
+        fn decode<D: bincode::de::Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
+            Ok(SerializableError::from_encoded(
+                Decode::decode(decoder)?,
+                Option::<Arc<SerializableError>>::decode(decoder)?,
+                Vec::<EncodedExtension>::decode(decoder)?,
+            ))
+        }
+    }
+    impl_borrow_decode!(SerializableError);
+}
@@ -0,0 +1,164 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    hash::Hash,
+    sync::{Mutex, MutexGuard},
+};
+
+use futures_util::future::{BoxFuture, FutureExt, Shared, WeakShared};
+
+use crate::ErrorContext;
+
+type FlightFuture<T> = BoxFuture<'static, Result<T, ErrorContext>>;
+type FlightMap<K, T> = HashMap<K, WeakShared<FlightFuture<T>>>;
+
+/// Deduplicates concurrent fallible computations
+///
+/// Concurrent callers of [`SingleFlight::run`] with the same key share a single in-flight
+/// computation, and all of them receive a clone of its result, including any [`ErrorContext`].
+/// Once the computation completes, the next call for that key starts a new one.
+///
+/// The computation is driven by whichever callers are currently awaiting it, independently of
+/// the runtime used. If the caller that started it (the leader) is dropped, the remaining callers
+/// keep driving it to completion. The computation itself is dropped (cancelled) only when every
+/// caller waiting for it has been dropped, in which case the next call starts a new computation.
+///
+/// ```
+/// # use cloneable_errors::{anyhow, SingleFlight};
+/// # futures::executor::block_on(async {
+/// let flights = SingleFlight::<u32, String>::new();
+/// let (tx, rx) = futures::channel::oneshot::channel::<()>();
+/// let (a, b, ()) = futures::join!(
+///     flights.run(1, || async {
+///         rx.await.unwrap();
+///         Err(anyhow!("backend {} unavailable", "xyz"))
+///     }),
+///     flights.run(1, || async { unreachable!("the computation is shared") }),
+///     async { tx.send(()).unwrap() },
+/// );
+/// // both callers received clones of the same error
+/// assert_eq!(a.unwrap_err(), b.unwrap_err());
+/// # });
+/// ```
+pub struct SingleFlight<K, T> {
+    in_flight: Mutex<FlightMap<K, T>>,
+}
+
+impl<K, T> Default for SingleFlight<K, T> {
+    fn default() -> Self {
+        SingleFlight {
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<K, T> SingleFlight<K, T>
+where
+    K: Hash + Eq + Clone,
+    T: Clone + Send + Sync + 'static,
+{
+    /// Creates a new, empty `SingleFlight`
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FlightMap<K, T>> {
+        self.in_flight.lock().expect("Internal lock got poisoned")
+    }
+
+    /// Returns the result of the in-flight computation for the given key, or starts a new one
+    /// using the given function if there is none.
+    ///
+    /// The function is called without holding the internal lock, so it may panic or use this
+    /// `SingleFlight`. If multiple callers race to start a computation for the same key, only the
+    /// first future to be stored is run, the others are dropped without being polled.
+    #[allow(clippy::missing_errors_doc)]
+    #[allow(clippy::missing_panics_doc)] // the only panic path would be a bug
+    pub async fn run<F, Fut>(&self, key: K, f: F) -> Result<T, ErrorContext>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, ErrorContext>> + Send + 'static,
+    {
+        let existing = self.lock().get(&key).and_then(WeakShared::upgrade);
+        let flight = if let Some(flight) = existing {
+            flight
+        } else {
+            let new_flight = f().boxed().shared();
+            let mut in_flight = self.lock();
+            // another caller may have started a computation while we were creating ours
+            if let Some(flight) = in_flight.get(&key).and_then(WeakShared::upgrade) {
+                flight
+            } else {
+                let weak = new_flight.downgrade().expect("a new future cannot be complete");
+                in_flight.insert(key.clone(), weak);
+                new_flight
+            }
+        };
+
+        let mut guard = FlightGuard {
+            owner: self,
+            key,
+            flight,
+            completed: false,
+        };
+        let result = guard.flight.clone().await;
+        guard.completed = true;
+        result
+    }
+
+    /// Returns the number of keys with a computation in flight
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().values().filter(|flight| flight.upgrade().is_some()).count()
+    }
+
+    /// Returns true if there are no computations in flight
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Removes the entry of a finished or abandoned flight from the map
+struct FlightGuard<'a, K, T>
+where
+    K: Hash + Eq + Clone,
+    T: Clone + Send + Sync + 'static,
+{
+    owner: &'a SingleFlight<K, T>,
+    key: K,
+    flight: Shared<FlightFuture<T>>,
+    completed: bool,
+}
+
+impl<K, T> Drop for FlightGuard<'_, K, T>
+where
+    K: Hash + Eq + Clone,
+    T: Clone + Send + Sync + 'static,
+{
+    fn drop(&mut self) {
+        // new callers can only obtain a reference to the flight while holding this lock,
+        // so the strong count can't change while we're checking it
+        let Ok(mut in_flight) = self.owner.in_flight.lock() else { return };
+        // we're the last caller waiting for an unfinished flight, it will be cancelled
+        let abandoned = self.flight.strong_count() == Some(1);
+        if !self.completed && !abandoned {
+            return;
+        }
+        let is_ours = in_flight
+            .get(&self.key)
+            .and_then(WeakShared::upgrade)
+            .is_some_and(|current| current.ptr_eq(&self.flight));
+        if is_ours {
+            in_flight.remove(&self.key);
+        }
+    }
+}
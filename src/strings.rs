@@ -5,25 +5,85 @@
 * Copyright (C) 2024-2025 mini_bomba
 */
 
-use std::{fmt::Display, ptr, sync::Arc};
+use alloc::{
+    borrow::Cow,
+    boxed::Box,
+    string::{String, ToString},
+    sync::Arc,
+};
+use core::{
+    fmt::{self, Debug, Display},
+    ops::Deref,
+    ptr,
+};
 
 
 /// A helper enum for easily cloneable strings
 ///
-/// NOTE: `SharedString`s are compared using pointer equality
+/// NOTE: `SharedString`s are compared using pointer equality, wrap them in [`crate::ByContent`]
+/// to compare them by value. Inline strings have no pointer to compare, so they are compared by
+/// value, like `ByContent` would.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum SharedString {
     Arc(Arc<str>),
     Static(&'static str),
+    /// A short string stored without any allocation, see [`InlineStr`]
+    Inline(InlineStr),
 }
 
-/// `SharedStrings` are compared using pointer equality.
+/// A string of at most [`InlineStr::CAPACITY`] bytes, stored inline
+///
+/// Used by [`SharedString`] to avoid allocating short messages.
+#[derive(Clone, Copy)]
+pub struct InlineStr {
+    len: u8,
+    buf: [u8; InlineStr::CAPACITY],
+}
+
+impl InlineStr {
+    /// The maximum length of an inline string, in bytes
+    ///
+    /// Chosen so that [`SharedString`] fits in 24 bytes, the size of a `String` on 64-bit targets.
+    pub const CAPACITY: usize = 22;
+
+    /// Copies the string into a new `InlineStr`, or returns `None` if it's too long
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)] // len <= CAPACITY
+    pub fn new(value: &str) -> Option<InlineStr> {
+        if value.len() > Self::CAPACITY {
+            return None;
+        }
+        let mut buf = [0; Self::CAPACITY];
+        buf[..value.len()].copy_from_slice(value.as_bytes());
+        Some(InlineStr {
+            len: value.len() as u8,
+            buf,
+        })
+    }
+
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)] // the only panic path would be a bug
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.buf[..usize::from(self.len)]).expect("BUG: InlineStr contains invalid UTF-8!")
+    }
+}
+
+impl Debug for InlineStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+/// `SharedStrings` are compared using pointer equality, except for inline strings.
 impl PartialEq for SharedString {
     fn eq(&self, other: &Self) -> bool {
         // Compare by pointer
         match (self, other) {
             (Self::Arc(this), Self::Arc(other)) => Arc::ptr_eq(this, other),
-            (Self::Static(this), Self::Static(other)) => ptr::eq(this, other),
+            (Self::Static(this), Self::Static(other)) => ptr::eq(*this, *other),
+            // no allocation to compare, copies are equal
+            (Self::Inline(this), Self::Inline(other)) => this.as_str() == other.as_str(),
             // different types
             _ => false
         }
@@ -32,11 +92,25 @@ impl PartialEq for SharedString {
 impl Eq for SharedString {}
 
 impl Display for SharedString {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.as_str())
     }
 }
 
+impl Deref for SharedString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for SharedString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
 impl From<&'static str> for SharedString {
     fn from(value: &'static str) -> Self {
         SharedString::Static(value)
@@ -49,10 +123,39 @@ impl From<Arc<str>> for SharedString {
     }
 }
 
+impl From<InlineStr> for SharedString {
+    fn from(value: InlineStr) -> Self {
+        SharedString::Inline(value)
+    }
+}
+
+/// Short strings are stored inline, longer ones are moved into an `Arc`.
 impl From<String> for SharedString
 {
     fn from(value: String) -> Self {
-        SharedString::Arc(Arc::from(value))
+        match InlineStr::new(&value) {
+            Some(inline) => SharedString::Inline(inline),
+            None => SharedString::Arc(Arc::from(value)),
+        }
+    }
+}
+
+/// Short strings are stored inline, longer ones are moved into an `Arc`.
+impl From<Box<str>> for SharedString {
+    fn from(value: Box<str>) -> Self {
+        match InlineStr::new(&value) {
+            Some(inline) => SharedString::Inline(inline),
+            None => SharedString::Arc(Arc::from(value)),
+        }
+    }
+}
+
+impl From<Cow<'static, str>> for SharedString {
+    fn from(value: Cow<'static, str>) -> Self {
+        match value {
+            Cow::Borrowed(s) => SharedString::Static(s),
+            Cow::Owned(s) => s.into(),
+        }
     }
 }
 
@@ -62,16 +165,58 @@ impl SharedString {
         match self {
             Self::Arc(s) => s,
             Self::Static(s) => s,
+            Self::Inline(s) => s.as_str(),
+        }
+    }
+
+    /// Creates a `SharedString` from formatting arguments, without allocating if possible
+    ///
+    /// Arguments without any placeholders become [`SharedString::Static`].
+    /// Prefer the [`crate::shared_format!`] macro.
+    #[must_use]
+    pub fn from_format_args(args: fmt::Arguments<'_>) -> Self {
+        match args.as_str() {
+            Some(s) => SharedString::Static(s),
+            None => args.to_string().into(),
         }
     }
 }
 
+/// Creates a [`SharedString`] using [`format!`] syntax
+///
+/// If no arguments are given, the format string is used directly as a
+/// [`SharedString::Static`]. Short results are stored inline.
+/// ```
+/// # use cloneable_errors::{shared_format, SharedString};
+/// assert!(matches!(shared_format!("no arguments"), SharedString::Static("no arguments")));
+/// let x = std::hint::black_box(1);
+/// assert!(matches!(shared_format!("x = {x}"), SharedString::Inline(_)));
+/// assert_eq!(shared_format!("x = {x}").as_str(), "x = 1");
+/// ```
+#[macro_export]
+macro_rules! shared_format {
+    ($($arg:tt)+) => {
+        $crate::SharedString::from_format_args(format_args!($($arg)+))
+    };
+}
+
+/// Turns a decoded string into an `Arc<str>`, using the global interner if set
+#[cfg(any(feature = "serde", feature = "bincode"))]
+fn intern_decoded(value: &str) -> Arc<str> {
+    #[cfg(feature = "std")]
+    if let Some(interner) = crate::global_interner() {
+        return interner.intern(value);
+    }
+    Arc::from(value)
+}
+
 // serde
 
 #[cfg(feature = "serde")]
 mod serde_impl {
+    use alloc::string::String;
     use serde::{Serialize, Deserialize};
-    use super::{SharedString, Arc};
+    use super::{intern_decoded, SharedString};
 
     impl Serialize for SharedString {
         fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -85,7 +230,8 @@ mod serde_impl {
         fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
         where D: serde::Deserializer<'de>
         {
-            Ok(Self::Arc(Arc::<str>::deserialize(deserializer)?))
+            let value = String::deserialize(deserializer)?;
+            Ok(Self::Arc(intern_decoded(&value)))
         }
     }
 }
@@ -94,8 +240,9 @@ mod serde_impl {
 
 #[cfg(feature = "bincode")]
 mod bincode_impl {
+    use alloc::string::String;
     use bincode::{Encode, Decode, impl_borrow_decode};
-    use super::SharedString;
+    use super::{intern_decoded, SharedString};
 
     impl Encode for SharedString {
         fn encode<E: bincode::enc::Encoder>(&self, encoder: &mut E) -> Result<(), bincode::error::EncodeError> {
@@ -105,7 +252,8 @@ mod bincode_impl {
 
     impl<Context> Decode<Context> for SharedString {
         fn decode<D: bincode::de::Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, bincode::error::DecodeError> {
-            Ok(SharedString::Arc(Decode::decode(decoder)?))
+            let value: String = Decode::decode(decoder)?;
+            Ok(SharedString::Arc(intern_decoded(&value)))
         }
     }
 
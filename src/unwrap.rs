@@ -0,0 +1,133 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use alloc::{boxed::Box, sync::Arc};
+use core::{error::Error, ops::Deref, ptr};
+#[cfg(feature = "std")]
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    RwLock,
+};
+
+use crate::{ErrorContext, ErrorIterator, SerializableError};
+
+/// A function that sees through a wrapper type, returning the error it wraps
+///
+/// Should return [`None`] if the given error is not of the wrapper type.
+pub type Unwrapper = for<'a> fn(&'a (dyn Error + 'static)) -> Option<&'a (dyn Error + 'static)>;
+
+#[cfg(feature = "std")]
+static UNWRAPPERS: RwLock<Vec<Unwrapper>> = RwLock::new(Vec::new());
+#[cfg(feature = "std")]
+static HAS_UNWRAPPERS: AtomicBool = AtomicBool::new(false);
+
+/// Teaches [`crate::ErrorIterator`] to see through a custom wrapper type
+///
+/// This makes downcasting the items of the iterator (and [`crate::IntoErrorIterator::find_extension`])
+/// work for errors hidden behind the wrapper. Unwrappers stay registered for the lifetime of the
+/// process, and are applied after the built-in ones.
+///
+/// For wrappers implementing [`Deref`], [`unwrap_deref`] can be used:
+/// ```
+/// # use std::{error::Error, fmt::Display, ops::Deref, sync::Arc};
+/// # use cloneable_errors::{register_unwrapper, unwrap_deref, IntoErrorIterator};
+/// #[derive(Debug)]
+/// struct MyError;
+/// # impl Display for MyError { fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { write!(f, "my error") } }
+/// impl Error for MyError {}
+///
+/// #[derive(Debug)]
+/// struct Wrapper(Arc<MyError>);
+/// # impl Display for Wrapper { fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result { self.0.fmt(f) } }
+/// impl Error for Wrapper {}
+/// impl Deref for Wrapper {
+///     type Target = MyError;
+///     fn deref(&self) -> &MyError { &self.0 }
+/// }
+///
+/// let error = Wrapper(Arc::new(MyError));
+/// assert!(error.error_chain().next().unwrap().downcast_ref::<MyError>().is_none());
+///
+/// register_unwrapper(unwrap_deref::<Wrapper>);
+/// assert!(error.error_chain().next().unwrap().downcast_ref::<MyError>().is_some());
+/// ```
+///
+/// Requires the `std` feature.
+///
+/// # Panics
+/// Will panic if the internal lock was poisoned.
+#[cfg(feature = "std")]
+pub fn register_unwrapper(unwrapper: Unwrapper) {
+    UNWRAPPERS.write().expect("Internal lock got poisoned").push(unwrapper);
+    HAS_UNWRAPPERS.store(true, Ordering::Release);
+}
+
+/// An [`Unwrapper`] for wrapper types that [`Deref`] to the wrapped error
+pub fn unwrap_deref<'a, W>(err: &'a (dyn Error + 'static)) -> Option<&'a (dyn Error + 'static)>
+where
+    W: Error + Deref + 'static,
+    W::Target: Error + Sized + 'static,
+{
+    err.downcast_ref::<W>().map(|wrapper| &**wrapper as &(dyn Error + 'static))
+}
+
+/// Built-in unwrappers, for the smart pointers most likely to be found in error chains
+///
+/// `Box<dyn Error>` and `Rc<dyn Error>` don't implement [`Error`], so they never appear in chains.
+static BUILTIN_UNWRAPPERS: &[Unwrapper] = &[
+    unwrap_arc_dyn,
+    unwrap_arc_dyn_send_sync,
+    unwrap_deref::<Arc<ErrorContext>>,
+    unwrap_deref::<Arc<SerializableError>>,
+    unwrap_deref::<Box<ErrorContext>>,
+    unwrap_deref::<Box<SerializableError>>,
+    #[cfg(feature = "anyhow")]
+    crate::context::unwrap_anyhow,
+];
+
+fn unwrap_arc_dyn<'a>(err: &'a (dyn Error + 'static)) -> Option<&'a (dyn Error + 'static)> {
+    err.downcast_ref::<Arc<dyn Error>>().map(|arc| &**arc)
+}
+
+fn unwrap_arc_dyn_send_sync<'a>(err: &'a (dyn Error + 'static)) -> Option<&'a (dyn Error + 'static)> {
+    err.downcast_ref::<Arc<dyn Error + Send + Sync>>()
+        .map(|arc| &**arc as &(dyn Error + 'static))
+}
+
+/// Casts away any known smart pointers wrapping the error
+///
+/// Stops at unwrappers returning the error they were given, and after
+/// [`ErrorIterator::DEFAULT_MAX_DEPTH`] steps, in case unwrappers form a cycle.
+pub(crate) fn unwrap_error<'a>(mut err: &'a (dyn Error + 'static)) -> &'a (dyn Error + 'static) {
+    for _ in 0..ErrorIterator::DEFAULT_MAX_DEPTH {
+        match unwrap_once(err) {
+            Some(inner) if !ptr::eq(inner, err) => err = inner,
+            _ => break,
+        }
+    }
+    err
+}
+
+/// Applies the first unwrapper that recognizes the error
+fn unwrap_once<'a>(err: &'a (dyn Error + 'static)) -> Option<&'a (dyn Error + 'static)> {
+    if let Some(inner) = BUILTIN_UNWRAPPERS.iter().find_map(|unwrapper| unwrapper(err)) {
+        return Some(inner);
+    }
+    #[cfg(feature = "std")]
+    if HAS_UNWRAPPERS.load(Ordering::Acquire) {
+        let unwrappers = UNWRAPPERS.read().ok()?;
+        return unwrappers.iter().find_map(|unwrapper| unwrapper(err));
+    }
+    None
+}
+
+/// Returns the given type name if the error is not a wrapper that [`unwrap_error`] sees through
+///
+/// The chain shows the unwrapped error, so the name of the wrapper type would be misleading.
+pub(crate) fn unwrapped_type_name(err: &(dyn Error + 'static), type_name: &'static str) -> Option<&'static str> {
+    ptr::addr_eq(unwrap_error(err), err).then_some(type_name)
+}
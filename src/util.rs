@@ -5,7 +5,7 @@
 * Copyright (C) 2025 mini_bomba
 */
 
-use std::sync::Arc;
+use alloc::sync::Arc;
 
 /// Compares two optional Arc<>s by address.
 ///
@@ -0,0 +1,88 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+#![cfg(feature = "anyhow")]
+
+use std::{error::Error, io};
+
+use cloneable_errors::{
+    AnyhowCause, AnyhowErrContext, AnyhowResContext, ErrContext, ErrorContext, IntoErrorIterator, SerializableError,
+};
+
+fn failing() -> anyhow::Result<()> {
+    let error = anyhow::Error::new(io::Error::other("disk on fire"));
+    Err(error.context("saving the file"))
+}
+
+#[test]
+fn test_anyhow_kept() {
+    let error = failing().context("handling the request").unwrap_err();
+    let messages: Vec<_> = error.error_chain().map(ToString::to_string).collect();
+    assert_eq!(messages, ["handling the request", "saving the file", "disk on fire"]);
+    assert!(error.error_chain().any(<dyn Error>::is::<io::Error>));
+    assert!(error.anyhow_backtrace().is_some());
+
+    let cause = error.cause().unwrap().downcast_ref::<AnyhowCause>().unwrap();
+    assert_eq!(cause.to_string(), "saving the file");
+    let _ = cause.backtrace();
+
+    // clones share the anyhow error
+    let copy = error.clone().context("outer");
+    assert!(copy.error_chain().any(<dyn Error>::is::<io::Error>));
+
+    let error = AnyhowErrContext::context(failing().unwrap_err(), "handling the request");
+    assert_eq!(error.error_chain().count(), 3);
+}
+
+#[test]
+fn test_anyhow_flattened() {
+    let error = failing().flattened_context("handling the request").unwrap_err();
+    let messages: Vec<_> = error.error_chain().map(ToString::to_string).collect();
+    assert_eq!(messages, ["handling the request", "saving the file", "disk on fire"]);
+    assert!(!error.error_chain().any(<dyn Error>::is::<io::Error>));
+    assert!(error.error_chain().skip(2).all(<dyn Error>::is::<SerializableError>));
+    assert!(error.anyhow_backtrace().is_none());
+
+    let error = ErrorContext::from(failing().unwrap_err());
+    assert_eq!(error.to_string(), "saving the file");
+    assert!(error.anyhow_backtrace().is_none());
+}
+
+#[test]
+fn test_into_anyhow() {
+    let error = io::Error::other("disk on fire").context("saving the file");
+    let converted = error.clone().into_anyhow().context("handling the request");
+
+    let messages: Vec<_> = converted.chain().map(ToString::to_string).collect();
+    assert_eq!(messages, ["handling the request", "saving the file", "disk on fire"]);
+    assert!(converted.chain().any(<dyn Error>::is::<io::Error>));
+    assert_eq!(converted.downcast_ref::<ErrorContext>(), Some(&error));
+    assert_eq!(ErrorContext::find_in_anyhow(&converted), Some(&error));
+    assert_eq!(ErrorContext::from(error.clone().into_anyhow()), error);
+}
+
+#[cfg(feature = "extensions")]
+#[test]
+fn test_into_anyhow_extensions() {
+    use std::sync::Arc;
+
+    use cloneable_errors::Extension;
+
+    #[derive(Debug, PartialEq)]
+    struct A(u32);
+    impl Extension for A {}
+
+    let error = io::Error::other("disk on fire").context_with_ext("saving the file", Arc::new(A(1)));
+    let converted = anyhow::Error::from(error);
+    let original = ErrorContext::find_in_anyhow(&converted).unwrap();
+    assert_eq!(original.find_extension::<A>().as_deref(), Some(&A(1)));
+
+    // into_anyhow matches the blanket conversion, extensions don't change the message or the chain
+    let converted = original.clone().into_anyhow();
+    assert_eq!(format!("{converted:#}"), "saving the file: disk on fire");
+    assert_eq!(converted.chain().count(), 2);
+    assert_eq!(converted.downcast_ref::<ErrorContext>().unwrap().find_extension::<A>().as_deref(), Some(&A(1)));
+}
@@ -42,3 +42,68 @@ fn shared_string_decoding_arc() {
         x => panic!("Expected SharedString::Arc(\"helo\"), got {x:?}")
     }
 }
+
+#[test]
+fn serializable_error_roundtrip() {
+    use cloneable_errors::{ErrContext, ErrorContext, IntoErrorIterator, SerializableError};
+
+    let config = bincode::config::standard();
+    let error = ErrorContext::new("inner").context("outer").serializable_copy();
+
+    let encoded = bincode::encode_to_vec(&error, config).unwrap();
+    let decoded: SerializableError = decode_from_slice(&encoded, config).unwrap().0;
+    assert_eq!(decoded, error);
+    assert_eq!(format!("{decoded:?}"), "outer\n\nCaused by:\n    0: inner");
+}
+
+#[cfg(all(feature = "derive", feature = "std"))]
+#[test]
+fn serializable_error_extensions_roundtrip() {
+    use std::{fmt::Display, num::ParseIntError, str::FromStr, sync::Arc};
+
+    use cloneable_errors::{
+        register_extension, ErrContext, ErrorContext, Extension, IntoErrorIterator, SerializableError,
+    };
+
+    #[derive(Debug, PartialEq, Eq, Extension)]
+    #[extension(copy, tag = "http_status", serialize)]
+    struct HttpStatus(u16);
+
+    impl Display for HttpStatus {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl FromStr for HttpStatus {
+        type Err = ParseIntError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            s.parse().map(HttpStatus)
+        }
+    }
+
+    #[derive(Extension)]
+    #[extension(copy)]
+    struct Unregistered;
+
+    register_extension::<HttpStatus>();
+
+    let config = bincode::config::standard();
+    let error = ErrorContext::new("inner")
+        .with_extension(Arc::new(HttpStatus(503)))
+        .context("outer")
+        .with_extension(Arc::new(Unregistered))
+        .without_extension::<HttpStatus>()
+        .serializable_copy();
+    assert!(error.find_extension::<Unregistered>().is_some());
+
+    let encoded = bincode::encode_to_vec(&error, config).unwrap();
+    let decoded: SerializableError = decode_from_slice(&encoded, config).unwrap().0;
+    assert_eq!(decoded, error);
+    // the mask is kept, unregistered extensions are dropped
+    assert!(decoded.find_extension::<HttpStatus>().is_none());
+    assert!(decoded.find_extension::<Unregistered>().is_none());
+    let cause = decoded.cause.as_deref().unwrap();
+    assert_eq!(cause.find_extension::<HttpStatus>().as_deref(), Some(&HttpStatus(503)));
+}
@@ -0,0 +1,116 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+#![cfg(feature = "std")]
+
+use std::{
+    cell::Cell,
+    io, ptr,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use futures::{executor::block_on, future::pending, poll};
+
+use cloneable_errors::{anyhow, CircuitBreaker, CircuitState, ErrContext, ErrorContext, IntoErrorIterator};
+
+static START: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+thread_local! {
+    static ELAPSED: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+/// A clock that only moves forward when [`advance`] is called on the current thread
+fn clock() -> Instant {
+    *START + ELAPSED.with(Cell::get)
+}
+
+fn advance(duration: Duration) {
+    ELAPSED.with(|elapsed| elapsed.set(elapsed.get() + duration));
+}
+
+fn timeout() -> Result<(), ErrorContext> {
+    Err(io::Error::from(io::ErrorKind::TimedOut).context("calling downstream"))
+}
+
+#[test]
+fn test_only_transient_errors_trip() {
+    let breaker = CircuitBreaker::new(2, Duration::from_secs(30));
+
+    for _ in 0..5 {
+        let _ = breaker.call(|| Err::<(), _>(anyhow!("invalid input")));
+    }
+    assert_eq!(breaker.state(), CircuitState::Closed);
+
+    let _ = breaker.call(timeout);
+    // a non-transient error means the downstream is responsive
+    let _ = breaker.call(|| Err::<(), _>(anyhow!("invalid input")));
+    let _ = breaker.call(timeout);
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    let tripping = breaker.call(timeout).unwrap_err();
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    // all rejected callers share the same cause, the tripping error
+    let a = breaker.call(|| -> Result<(), _> { unreachable!() }).unwrap_err();
+    let b = breaker.call(|| -> Result<(), _> { unreachable!() }).unwrap_err();
+    let open_error = breaker.open_error().unwrap();
+    assert_eq!(a.to_string(), open_error.to_string());
+    let cause = a.error_chain().nth(1).unwrap();
+    assert!(ptr::addr_eq(cause, b.error_chain().nth(1).unwrap()));
+    assert!(ptr::addr_eq(cause, open_error.error_chain().nth(1).unwrap()));
+    assert_eq!(cause.downcast_ref::<ErrorContext>().unwrap(), &tripping);
+
+    breaker.reset();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert!(breaker.open_error().is_none());
+}
+
+#[test]
+fn test_half_open_probing() {
+    let breaker = CircuitBreaker::new(1, Duration::from_secs(20)).with_clock(clock);
+    let _ = breaker.call(timeout);
+    assert_eq!(breaker.state(), CircuitState::Open);
+    advance(Duration::from_secs(19));
+    assert_eq!(breaker.state(), CircuitState::Open);
+    advance(Duration::from_secs(1));
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+    // failed probe
+    let _ = breaker.call(timeout);
+    assert_eq!(breaker.state(), CircuitState::Open);
+    advance(Duration::from_secs(20));
+
+    // cancelled probe
+    block_on(async {
+        let mut probe = Box::pin(breaker.call_async(pending::<Result<(), ErrorContext>>));
+        assert!(poll!(&mut probe).is_pending());
+        // only one probe at a time
+        assert!(breaker.call_async(|| async { Ok(()) }).await.is_err());
+        drop(probe);
+    });
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+
+    // successful probe
+    assert_eq!(breaker.call(|| Ok(1)).unwrap(), 1);
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[cfg(feature = "extensions")]
+#[test]
+fn test_open_error_retry_after() {
+    use cloneable_errors::RetryAfter;
+
+    let breaker = CircuitBreaker::new(1, Duration::from_secs(5)).with_clock(clock);
+    let _ = breaker.call(timeout);
+    let error = breaker.call(|| Ok(())).unwrap_err();
+    assert_eq!(error.find_extension::<RetryAfter>().unwrap().0, Duration::from_secs(5));
+
+    // the hint is the time remaining until the breaker becomes half-open
+    advance(Duration::from_secs(3));
+    let error = breaker.call(|| Ok(())).unwrap_err();
+    assert_eq!(error.find_extension::<RetryAfter>().unwrap().0, Duration::from_secs(2));
+    assert!(breaker.open_error().unwrap().find_extension::<RetryAfter>().is_none());
+}
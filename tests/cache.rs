@@ -0,0 +1,120 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+#![cfg(feature = "std")]
+
+use std::{
+    cell::Cell,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use cloneable_errors::{anyhow, CacheStats, ErrorCache, IntoErrorIterator};
+
+static START: LazyLock<Instant> = LazyLock::new(Instant::now);
+
+thread_local! {
+    static ELAPSED: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+/// A clock that only moves forward when [`advance`] is called on the current thread
+fn clock() -> Instant {
+    *START + ELAPSED.with(Cell::get)
+}
+
+fn advance(duration: Duration) {
+    ELAPSED.with(|elapsed| elapsed.set(elapsed.get() + duration));
+}
+
+#[test]
+fn test_cache_ttls() {
+    let cache = ErrorCache::new(Duration::from_mins(1), Duration::from_secs(20)).with_clock(clock);
+    let error = anyhow!(String::from("not found"));
+
+    cache.insert("ok", Ok(1));
+    cache.insert("err", Err(error.clone()));
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.error_bytes(), error.estimated_size());
+
+    assert_eq!(cache.get("ok"), Some(Ok(1)));
+    // the same error is handed out
+    assert_eq!(cache.get("err"), Some(Err(error)));
+    assert_eq!(cache.get("missing"), None);
+
+    advance(Duration::from_secs(20));
+    assert_eq!(cache.get("err"), None);
+    assert_eq!(cache.get("ok"), Some(Ok(1)));
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.error_bytes(), 0);
+
+    assert_eq!(cache.stats(), CacheStats { hits: 2, error_hits: 1, misses: 2, evictions: 0 });
+}
+
+#[test]
+fn test_cache_disabled_variant() {
+    let cache = ErrorCache::new(Duration::from_mins(1), Duration::ZERO);
+    let mut calls = 0;
+    for _ in 0..3 {
+        let _ = cache.get_or_insert_with(1, || {
+            calls += 1;
+            Err::<(), _>(anyhow!("failed"))
+        });
+    }
+    assert_eq!(calls, 3);
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_cache_size_limit() {
+    let size = anyhow!(String::from("error 1")).estimated_size();
+    let cache = ErrorCache::new(Duration::from_mins(1), Duration::from_mins(1))
+        .with_max_error_bytes(size * 2)
+        .with_clock(clock);
+
+    for i in 1..=3 {
+        cache.insert(i, Err::<(), _>(anyhow!("error {}", i)));
+        advance(Duration::from_secs(1));
+    }
+    // the error closest to expiring was evicted
+    assert_eq!(cache.get(&1), None);
+    assert!(cache.get(&2).is_some());
+    assert!(cache.get(&3).is_some());
+    assert_eq!(cache.error_bytes(), size * 2);
+    assert_eq!(cache.stats().evictions, 1);
+
+    // successes are not limited
+    for i in 4..=10 {
+        cache.insert(i, Ok(()));
+    }
+    assert_eq!(cache.len(), 9);
+}
+
+#[cfg(feature = "extensions")]
+#[test]
+fn test_cache_ttl_extension() {
+    use std::sync::Arc;
+
+    use cloneable_errors::{CacheTtl, ErrContext};
+
+    let cache = ErrorCache::new(Duration::from_mins(1), Duration::from_mins(1)).with_clock(clock);
+    let permanent = anyhow!("permanent failure", extend: Arc::new(CacheTtl(Duration::from_secs(20))));
+    let transient = anyhow!("transient failure", extend: Arc::new(CacheTtl(Duration::from_hours(1))));
+
+    cache.insert(1, Err::<(), _>(permanent.context("outer layer")));
+    cache.insert(2, Err(anyhow!("not cached", extend: Arc::new(CacheTtl(Duration::ZERO)))));
+    cache.insert(3, Err(transient));
+    assert!(cache.get(&1).is_some());
+    assert_eq!(cache.get(&2), None);
+    assert!(cache.get(&3).is_some());
+
+    advance(Duration::from_secs(20));
+    assert_eq!(cache.get(&1), None);
+    assert!(cache.get(&3).is_some());
+
+    // the extension can only shorten the failure TTL
+    advance(Duration::from_mins(1));
+    assert_eq!(cache.get(&3), None);
+}
@@ -0,0 +1,50 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use std::{
+    collections::{BTreeSet, HashSet},
+    io,
+    sync::Arc,
+};
+
+use cloneable_errors::{anyhow, ByContent, ErrContext, IntoErrorIterator, SharedString};
+
+#[test]
+fn test_shared_string_by_content() {
+    let a = SharedString::from(Arc::<str>::from("helo"));
+    let b = SharedString::from(Arc::<str>::from("helo"));
+    assert_ne!(a, b);
+    assert_eq!(ByContent(a.clone()), ByContent(b.clone()));
+    assert_eq!(ByContent(a), ByContent(SharedString::Static("helo")));
+
+    let set: BTreeSet<_> = ["b", "a", "b"].into_iter().map(SharedString::from).map(ByContent).collect();
+    let sorted: Vec<_> = set.iter().map(|s| s.as_str()).collect();
+    assert_eq!(sorted, ["a", "b"]);
+    assert!(set.contains("a"));
+}
+
+#[test]
+fn test_errors_by_content() {
+    let a = io::Error::other("disk full").context("writing file");
+    let b = io::Error::other("disk full").context("writing file");
+    let c = io::Error::other("disk on fire").context("writing file");
+    assert_ne!(a, b);
+    assert_eq!(ByContent(a.clone()), ByContent(b.clone()));
+    assert_ne!(ByContent(a.clone()), ByContent(c.clone()));
+    assert!(ByContent(a.clone()) < ByContent(c.clone()));
+    // prefixes are ordered first
+    assert!(ByContent(anyhow!("writing file")) < ByContent(a.clone()));
+
+    let set: HashSet<_> = [a.clone(), b, c].into_iter().map(ByContent).collect();
+    assert_eq!(set.len(), 2);
+
+    assert!(set.contains(&ByContent(a.clone())));
+
+    // SerializableError is compared by value without a wrapper
+    let copies: HashSet<_> = [a.serializable_copy(), a.serializable_copy()].into();
+    assert_eq!(copies.len(), 1);
+}
@@ -0,0 +1,99 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use std::{error::Error, fmt::Display};
+
+use cloneable_errors::{anyhow, ErrContext, IntoErrorIterator, Truncation};
+
+/// An error with a broken `source` implementation
+#[derive(Debug)]
+struct Node {
+    name: &'static str,
+    next: &'static Node,
+}
+
+static A: Node = Node { name: "a", next: &B };
+static B: Node = Node { name: "b", next: &A };
+
+impl Display for Node {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Error for Node {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(self.next)
+    }
+}
+
+#[derive(Debug)]
+struct Leaf(u32);
+
+impl Display for Leaf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "leaf {}", self.0)
+    }
+}
+
+impl Error for Leaf {}
+
+/// An error whose source is stored at the same address
+#[derive(Debug)]
+#[repr(C)]
+struct Wrapper {
+    leaf: Leaf,
+}
+
+impl Display for Wrapper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "wrapper")
+    }
+}
+
+impl Error for Wrapper {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.leaf)
+    }
+}
+
+#[test]
+fn test_cycle_detection() {
+    let mut iter = A.error_chain();
+    let names: Vec<_> = iter.by_ref().map(ToString::to_string).collect();
+    // the starting error may be repeated once, if the compiler duplicated its vtable
+    assert!(names == ["a", "b"] || names == ["a", "b", "a"], "{names:?}");
+    assert_eq!(iter.truncation(), Some(Truncation::Cycle));
+
+    let error = (&A).context("outer");
+    assert!(format!("{error:?}").ends_with("\n    <cycle detected in the error chain>"));
+    let copy = error.serializable_copy();
+    assert!(copy.error_chain().count() <= 5);
+    assert_eq!(copy.error_chain().last().unwrap().to_string(), Truncation::Cycle.to_string());
+
+    // same address, different error
+    let mut iter = Wrapper { leaf: Leaf(1) }.error_chain();
+    assert_eq!(iter.by_ref().count(), 2);
+    assert_eq!(iter.truncation(), None);
+}
+
+#[test]
+fn test_depth_limit() {
+    let mut error = anyhow!("root");
+    for i in 0..20 {
+        error = error.context(format!("layer {i}"));
+    }
+
+    let mut iter = error.error_chain();
+    assert_eq!(iter.by_ref().count(), 21);
+    assert_eq!(iter.truncation(), None);
+
+    let mut iter = error.error_chain().with_max_depth(5);
+    assert_eq!(iter.by_ref().count(), 5);
+    assert_eq!(iter.truncation(), Some(Truncation::DepthLimit));
+    assert_eq!(error.error_chain().with_max_depth(5).serializable_copy().error_chain().count(), 6);
+}
@@ -0,0 +1,135 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+#![cfg(feature = "derive")]
+
+use std::{
+    fmt::{Display, Formatter},
+    num::ParseIntError,
+    str::FromStr,
+    sync::Arc,
+};
+
+use cloneable_errors::{ErrContext, ErrorContext, Extension, IntoErrorIterator, SerializableExtension};
+
+#[derive(Extension)]
+#[extension(display, tag = "http_status")]
+struct HttpStatus(u16);
+
+impl Display for HttpStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HTTP {}", self.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Extension)]
+#[extension(merge = "sum")]
+struct Retries {
+    count: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Extension)]
+#[extension(merge = "union")]
+struct Trail(Vec<&'static str>);
+
+#[derive(Clone, Debug, PartialEq, Eq, Extension)]
+#[extension(merge = "Deadline::earliest")]
+struct Deadline(u64);
+
+impl Deadline {
+    fn earliest(&self, deeper: &Self) -> Self {
+        Deadline(self.0.min(deeper.0))
+    }
+}
+
+#[derive(Extension)]
+struct Plain;
+
+#[test]
+fn test_derived_extension() {
+    let status = HttpStatus(503);
+    assert_eq!(status.tag(), Some("http_status"));
+    assert!(status.as_debug().is_none());
+    assert_eq!(status.as_display().map(ToString::to_string).as_deref(), Some("HTTP 503"));
+
+    assert_eq!(Plain.tag(), None);
+    assert!(Plain.as_display().is_none());
+
+    let error = ErrorContext::new("unavailable")
+        .with_extension(Arc::new(status))
+        .with_extension(Arc::new(Plain));
+    assert_eq!(format!("{error:?}"), "unavailable\n    - HTTP 503");
+}
+
+#[test]
+fn test_derived_merge() {
+    let error = ErrorContext::new("inner")
+        .with_extension(Arc::new(Retries { count: 2 }))
+        .with_extension(Arc::new(Trail(vec!["db"])))
+        .with_extension(Arc::new(Deadline(30)))
+        .context("outer")
+        .with_extension(Arc::new(Retries { count: 3 }))
+        .with_extension(Arc::new(Trail(vec!["api"])))
+        .with_extension(Arc::new(Deadline(60)));
+
+    assert_eq!(error.merged_extension::<Retries>().as_deref(), Some(&Retries { count: 5 }));
+    assert_eq!(error.merged_extension::<Trail>().as_deref(), Some(&Trail(vec!["api", "db"])));
+    assert_eq!(error.merged_extension::<Deadline>().as_deref(), Some(&Deadline(30)));
+}
+
+#[test]
+fn test_derived_copy() {
+    #[derive(Extension)]
+    #[extension(copy)]
+    struct RemoteService(&'static str);
+
+    assert!(RemoteService("auth").is_copyable());
+    assert!(!Plain.is_copyable());
+
+    let error = ErrorContext::new("inner")
+        .with_extension(Arc::new(RemoteService("auth")))
+        .with_extension(Arc::new(Plain))
+        .context("outer");
+    let copy = error.serializable_copy();
+    assert_eq!(copy.find_extension::<RemoteService>().map(|ext| ext.0), Some("auth"));
+    assert!(copy.find_extension::<Plain>().is_none());
+}
+
+#[test]
+fn test_derived_serialize() {
+    #[derive(Debug, PartialEq, Eq, Extension)]
+    #[extension(tag = "retry_count", serialize)]
+    struct RetryCount(u32);
+
+    impl Display for RetryCount {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    impl FromStr for RetryCount {
+        type Err = ParseIntError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            s.parse().map(RetryCount)
+        }
+    }
+
+    /// Displays the encoded form of an extension
+    struct Encoded<'a>(&'a RetryCount);
+
+    impl Display for Encoded<'_> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            self.0.encode(f)
+        }
+    }
+
+    assert_eq!(RetryCount::TAG, "retry_count");
+    assert_eq!(RetryCount(3).tag(), Some(RetryCount::TAG));
+    assert_eq!(Encoded(&RetryCount(3)).to_string(), "3");
+    assert_eq!(RetryCount::decode("3"), Some(RetryCount(3)));
+    assert_eq!(RetryCount::decode("three"), None);
+}
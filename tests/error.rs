@@ -0,0 +1,112 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use std::io;
+
+use cloneable_errors::{anyhow, bail, ErrContext, Error, ErrorContext, IntoErrorIterator, ResContext};
+
+fn parse(input: &str) -> Result<u32, Error> {
+    let value: u32 = input.parse()?;
+    if value == 0 {
+        bail!("zero is not allowed");
+    }
+    Ok(value)
+}
+
+#[test]
+fn test_question_mark() {
+    assert_eq!(parse("5").unwrap(), 5);
+
+    let error = parse("x").unwrap_err();
+    assert!(error.downcast_ref::<std::num::ParseIntError>().is_some());
+    assert_eq!(error.error_chain().count(), 1);
+    assert_eq!(error.type_name(), std::any::type_name::<std::num::ParseIntError>());
+
+    let error = parse("0").unwrap_err();
+    assert_eq!(error.to_string(), "zero is not allowed");
+    assert!(error.downcast_ref::<ErrorContext>().is_some());
+
+    let error = Error::msg("plain message");
+    assert_eq!(format!("{error:?}"), "plain message");
+}
+
+#[test]
+fn test_error_context() {
+    let error = Error::from(io::Error::other("disk on fire"));
+    assert_eq!(error.clone(), error);
+    assert_ne!(Error::from(io::Error::other("disk on fire")), error);
+
+    let wrapped = Err::<(), _>(error.clone()).context("writing the file").unwrap_err();
+    assert_eq!(format!("{wrapped:?}"), "writing the file\n\nCaused by:\n    0: disk on fire");
+    assert_eq!(
+        wrapped.links().nth(1).unwrap().type_name(),
+        Some(std::any::type_name::<io::Error>())
+    );
+    assert_eq!(error.serializable_copy().to_string(), "disk on fire");
+
+    let error: Error = anyhow!("inner").context("outer").into();
+    assert_eq!(format!("{error:?}"), "outer\n\nCaused by:\n    0: inner");
+}
+
+#[test]
+fn test_into_error_context() {
+    fn checked(input: &str) -> Result<u32, ErrorContext> {
+        Ok(parse(input)?)
+    }
+
+    // wrapped ErrorContexts are returned as-is
+    let error = parse("0").unwrap_err();
+    let converted = checked("0").unwrap_err();
+    assert_eq!(Some(&converted), error.downcast_ref::<ErrorContext>());
+
+    // other errors are flattened
+    let converted = checked("x").unwrap_err();
+    assert_eq!(converted.to_string(), "invalid digit found in string");
+    assert_eq!(converted.error_chain().count(), 1);
+    assert!(!converted.error_chain().any(<dyn std::error::Error>::is::<std::num::ParseIntError>));
+}
+
+#[cfg(feature = "extensions")]
+#[test]
+fn test_extensions() {
+    use std::{fmt, sync::Arc};
+
+    use cloneable_errors::Extension;
+
+    #[derive(Debug, PartialEq)]
+    struct A(u32);
+    impl Extension for A {}
+
+    #[derive(Debug)]
+    struct Foreign;
+    impl fmt::Display for Foreign {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "foreign")
+        }
+    }
+    impl std::error::Error for Foreign {}
+
+    let error: Error = Foreign.context("outer").with_extension(Arc::new(A(1))).into();
+    assert_eq!(error.find_extension::<A>().as_deref(), Some(&A(1)));
+    assert_eq!(error.get_extension::<A>().as_deref(), Some(&A(1)));
+
+    let error = Error::from(Foreign).context_with_ext("outer", Arc::new(A(2)));
+    assert_eq!(error.get_extension::<A>().as_deref(), Some(&A(2)));
+    assert_eq!(error.error_chain().count(), 2);
+
+    // extensions are attached to the wrapped ErrorContext, without adding a layer
+    let error = Error::msg("plain message").with_extension(Arc::new(A(3)));
+    assert_eq!(error.get_extension::<A>().as_deref(), Some(&A(3)));
+    assert_eq!(error.error_chain().count(), 1);
+    assert_eq!(error.to_string(), "plain message");
+
+    let mut error = Error::from(Foreign);
+    error.add_extension(Arc::new(A(4)));
+    assert_eq!(error.get_extension::<A>().as_deref(), Some(&A(4)));
+    assert_eq!(error.error_chain().count(), 1);
+    assert_eq!(error.to_string(), "foreign");
+}
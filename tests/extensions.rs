@@ -126,3 +126,242 @@ fn test_layered_extensions() {
     assert_eq!(layer2.find_extension::<B>().as_ref(), Some(&b2));
     assert!(Arc::ptr_eq(&layer2.find_extension::<B>().unwrap(), &b2));
 }
+
+#[test]
+fn test_all_extensions() {
+    let inner = ErrorContext::new("inner").with_extension(Arc::new(B(1)));
+    let middle = inner.context("middle").with_extension(Arc::new(A));
+    let outer = middle.clone().context("outer").with_extension(Arc::new(B(3)));
+
+    let all: Vec<_> = outer.find_all_extensions::<B>().map(|b| b.0).collect();
+    assert_eq!(all, [3, 1]);
+    assert_eq!(outer.find_root_extension::<B>().as_deref(), Some(&B(1)));
+    assert_eq!(outer.get_extension::<B>().as_deref(), Some(&B(3)));
+    assert_eq!(outer.get_extension::<A>(), None);
+    assert_eq!(middle.get_extension::<A>().as_deref(), Some(&A));
+
+    // masks hide every deeper instance
+    let masked = middle.without_extension::<B>().context("outer").with_extension(Arc::new(B(3)));
+    let all: Vec<_> = masked.find_all_extensions::<B>().map(|b| b.0).collect();
+    assert_eq!(all, [3]);
+    assert_eq!(masked.find_root_extension::<B>().as_deref(), Some(&B(3)));
+
+    // an instance added after masking is still visible
+    let readded = masked.context("top").without_extension::<B>().with_extension(Arc::new(B(4)));
+    let all: Vec<_> = readded.find_all_extensions::<B>().map(|b| b.0).collect();
+    assert_eq!(all, [4]);
+}
+
+#[test]
+fn test_merged_extensions() {
+    use cloneable_errors::MergeExtension;
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Sum(u32);
+    impl Extension for Sum {}
+    impl MergeExtension for Sum {
+        fn merge(&self, deeper: &Self) -> Self {
+            Sum(self.0 + deeper.0)
+        }
+    }
+
+    let error = ErrorContext::new("inner");
+    assert_eq!(error.merged_extension::<Sum>(), None);
+
+    let single = Arc::new(Sum(1));
+    let error = error.with_extension(single.clone());
+    assert!(Arc::ptr_eq(&error.merged_extension::<Sum>().unwrap(), &single));
+
+    let error = error
+        .context("middle")
+        .with_extension(Arc::new(Sum(2)))
+        .context("outer")
+        .with_extension(Arc::new(Sum(4)));
+    assert_eq!(error.merged_extension::<Sum>().as_deref(), Some(&Sum(7)));
+
+    // masked instances are not merged
+    let error = error.without_extension::<Sum>().context("top").with_extension(Arc::new(Sum(8)));
+    assert_eq!(error.merged_extension::<Sum>().as_deref(), Some(&Sum(8)));
+}
+
+#[test]
+fn test_rendered_extensions() {
+    use std::fmt::{Debug, Display};
+
+    #[derive(Debug)]
+    #[allow(dead_code)] // only read by the Debug impl
+    struct HttpStatus(u16);
+    impl Extension for HttpStatus {
+        fn as_debug(&self) -> Option<&dyn Debug> {
+            Some(self)
+        }
+    }
+
+    struct Note(&'static str);
+    impl Extension for Note {
+        fn as_display(&self) -> Option<&dyn Display> {
+            Some(&self.0)
+        }
+    }
+
+    let error = ErrorContext::new("inner")
+        .with_extension(Arc::new(HttpStatus(404)))
+        .with_extension(Arc::new(A))
+        .context("outer")
+        .with_extension(Arc::new(Note("while fetching the user")))
+        .without_extension::<A>();
+    assert_eq!(
+        format!("{error:?}"),
+        "outer\n    - while fetching the user\n\nCaused by:\n    0: inner\n       - HttpStatus(404)"
+    );
+
+    // masks are not listed
+    let mut names: Vec<_> = error.extensions().map(|(name, _)| name).collect();
+    assert_eq!(names, [std::any::type_name::<Note>()]);
+    let inner = error.error_chain().nth(1).unwrap().downcast_ref::<ErrorContext>().unwrap();
+    names = inner.extensions().map(|(name, _)| name).collect();
+    names.sort_unstable();
+    let mut expected = [std::any::type_name::<A>(), std::any::type_name::<HttpStatus>()];
+    expected.sort_unstable();
+    assert_eq!(names, expected);
+}
+
+#[test]
+fn test_mutable_extensions() {
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct Log(Vec<&'static str>);
+    impl Extension for Log {}
+
+    let inner = ErrorContext::new("inner").with_extension(Arc::new(Log(vec!["inner"])));
+    let mut error = inner.clone().context("outer");
+
+    // extensions of causes are not touched
+    assert_eq!(error.extension_mut::<Log>(), None);
+    assert_eq!(error.update_extension(|log: &mut Log| log.0.len()), None);
+
+    let mut modified = inner.clone();
+    assert_eq!(modified.update_extension(|log: &mut Log| { log.0.push("retry"); log.0.len() }), Some(2));
+    assert_eq!(modified.get_extension::<Log>().as_deref(), Some(&Log(vec!["inner", "retry"])));
+    assert_eq!(inner.get_extension::<Log>().as_deref(), Some(&Log(vec!["inner"])));
+    assert_eq!(error.find_extension::<Log>().as_deref(), Some(&Log(vec!["inner"])));
+
+    // unique extensions are modified in place
+    let before = modified.get_extension::<Log>().map(|log| Arc::as_ptr(&log));
+    modified.extension_mut::<Log>().unwrap().0.clear();
+    assert_eq!(modified.get_extension::<Log>().map(|log| Arc::as_ptr(&log)), before);
+    assert_eq!(modified.get_extension::<Log>().as_deref(), Some(&Log(vec![])));
+}
+
+#[test]
+fn test_serializable_extensions() {
+    use cloneable_errors::SerializableError;
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Copied;
+    impl Extension for Copied {
+        fn is_copyable(&self) -> bool {
+            true
+        }
+    }
+
+    let received = SerializableError::new("remote failure").with_extension(Arc::new(A));
+    assert_eq!(received.find_extension::<A>().as_deref(), Some(&A));
+    assert_eq!(received.extensions().count(), 1);
+    assert!(received.extension_map().is_some());
+    assert!(SerializableError::new("no extensions").extension_map().is_none());
+
+    // found through ErrorContext layers, and masked by them
+    let error = received.clone().context("calling the service");
+    assert_eq!(error.find_extension::<A>().as_deref(), Some(&A));
+    assert_eq!(error.get_extension::<A>(), None);
+    assert_eq!(error.clone().without_extension::<A>().find_extension::<A>(), None);
+
+    // only copyable extensions (and masks hiding them) are carried over by serializable_copy
+    let error = ErrorContext::new("inner")
+        .with_extension(Arc::new(Copied))
+        .with_extension(Arc::new(B(1)))
+        .context("middle")
+        .without_extension::<Copied>()
+        .context("outer");
+    let copy = error.serializable_copy();
+    assert_eq!(copy.find_extension::<B>(), None);
+    assert_eq!(copy.find_extension::<Copied>(), None);
+    assert_eq!(copy.error_chain().nth(2).unwrap().downcast_ref::<SerializableError>().unwrap().find_extension::<Copied>().as_deref(), Some(&Copied));
+
+    // extensions don't affect equality
+    assert_eq!(received, SerializableError::new("remote failure"));
+    assert_eq!(received.clone().without_extension::<A>().find_extension::<A>(), None);
+}
+
+#[test]
+fn test_many_extensions() {
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct N<const I: usize>(usize);
+    impl<const I: usize> Extension for N<I> {}
+
+    macro_rules! for_each_n {
+        ($m:ident) => { $m!(0); $m!(1); $m!(2); $m!(3); $m!(4); $m!(5); $m!(6); $m!(7); $m!(8); $m!(9); $m!(10); $m!(11) };
+    }
+
+    let mut error = ErrorContext::new("many");
+    macro_rules! add { ($i:literal) => { error.add_extension(Arc::new(N::<$i>($i))); }; }
+    for_each_n!(add);
+    assert_eq!(error.extensions().count(), 12);
+
+    // modifying a clone leaves the original untouched
+    let original = error.clone();
+    macro_rules! bump { ($i:literal) => { error.extension_mut::<N<$i>>().unwrap().0 += 100; }; }
+    for_each_n!(bump);
+    macro_rules! check { ($i:literal) => {
+        assert_eq!(original.get_extension::<N<$i>>().as_deref(), Some(&N::<$i>($i)));
+        assert_eq!(error.get_extension::<N<$i>>().as_deref(), Some(&N::<$i>($i + 100)));
+    }; }
+    for_each_n!(check);
+
+    // remove every extension, shrinking the map back down
+    macro_rules! remove { ($i:literal) => {
+        assert_eq!(error.remove_extension::<N<$i>>().as_deref(), Some(&N::<$i>($i + 100)));
+        assert_eq!(error.get_extension::<N<$i>>(), None);
+        assert_eq!(original.get_extension::<N<$i>>().as_deref(), Some(&N::<$i>($i)));
+    }; }
+    for_each_n!(remove);
+    assert_eq!(error.extensions().count(), 0);
+    assert_eq!(original.extensions().count(), 12);
+}
+
+#[test]
+fn test_mask_tombstones() {
+    let inner = ErrorContext::new("inner").with_extension(Arc::new(B(1)));
+    let mut error = inner.context("outer");
+
+    assert_eq!(error.remove_extension::<B>().as_deref(), Some(&B(1)));
+    assert_eq!(error.find_extension::<B>(), None);
+    assert_eq!(error.extensions().count(), 0);
+
+    // re-adding and removing the extension again keeps the deeper instance hidden
+    error.add_extension(Arc::new(B(2)));
+    assert_eq!(error.find_extension::<B>().as_deref(), Some(&B(2)));
+    assert_eq!(error.remove_extension::<B>().as_deref(), Some(&B(2)));
+    assert_eq!(error.find_extension::<B>(), None);
+    assert_eq!(error.remove_extension::<B>(), None);
+}
+
+#[test]
+fn test_context_with_extensions() {
+    use cloneable_errors::ResContextExtensions;
+
+    let missing: Option<u32> = None;
+    let error = missing.context_with_ext("value missing", Arc::new(A)).unwrap_err();
+    assert_eq!(format!("{error:?}"), "value missing");
+    assert_eq!(error.get_extension::<A>().as_deref(), Some(&A));
+    assert_eq!(Some(1).context_with_ext("value missing", Arc::new(A)), Ok(1));
+
+    let failed: Result<(), _> = Err(std::io::Error::other("disk on fire"));
+    let error = failed.with_context_ext(|| ("saving", Arc::new(B(5)))).unwrap_err();
+    assert_eq!(format!("{error:?}"), "saving\n\nCaused by:\n    0: disk on fire");
+    assert_eq!(error.get_extension::<B>().as_deref(), Some(&B(5)));
+
+    // the function is not called for successful results
+    let ok: Result<u32, std::io::Error> = Ok(2);
+    assert_eq!(ok.with_context_ext(|| -> (&str, Arc<dyn Extension>) { unreachable!() }), Ok(2));
+}
@@ -0,0 +1,59 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+#![cfg(feature = "eyre")]
+
+use std::{error::Error, io};
+
+use cloneable_errors::{ErrContext, ErrorContext, IntoErrorIterator};
+
+#[test]
+fn test_into_eyre() {
+    let error = io::Error::other("disk on fire").context("saving the file");
+    let report = error.clone().into_eyre().wrap_err("handling the request");
+
+    let messages: Vec<_> = report.chain().map(ToString::to_string).collect();
+    assert_eq!(messages, ["handling the request", "saving the file", "disk on fire"]);
+    assert!(report.chain().any(<dyn Error>::is::<io::Error>));
+    assert_eq!(ErrorContext::find_in_eyre(&report), Some(&error));
+
+    let report: eyre::Report = error.clone().into();
+    assert_eq!(report.downcast::<ErrorContext>().unwrap(), error);
+}
+
+#[test]
+fn test_from_eyre() {
+    let error = io::Error::other("disk on fire").context("saving the file");
+    assert_eq!(ErrorContext::from(error.clone().into_eyre()), error);
+
+    // other reports are flattened
+    let report = eyre::Report::new(io::Error::other("disk on fire")).wrap_err("saving the file");
+    let converted = ErrorContext::from(report);
+    assert_eq!(format!("{converted:?}"), "saving the file\n\nCaused by:\n    0: disk on fire");
+    assert!(!converted.error_chain().any(<dyn Error>::is::<io::Error>));
+}
+
+#[cfg(feature = "extensions")]
+#[test]
+fn test_into_eyre_extensions() {
+    use std::sync::Arc;
+
+    use cloneable_errors::Extension;
+
+    #[derive(Debug, PartialEq)]
+    struct HttpStatus(u16);
+    impl Extension for HttpStatus {
+        fn as_debug(&self) -> Option<&dyn std::fmt::Debug> {
+            Some(self)
+        }
+    }
+
+    let error = io::Error::other("disk on fire").context_with_ext("saving the file", Arc::new(HttpStatus(503)));
+    let report = error.into_eyre().wrap_err("handling the request");
+    assert_eq!(format!("{report:#}"), "handling the request: saving the file: disk on fire");
+    let original = ErrorContext::find_in_eyre(&report).unwrap();
+    assert_eq!(original.find_extension::<HttpStatus>().as_deref(), Some(&HttpStatus(503)));
+}
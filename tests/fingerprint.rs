@@ -0,0 +1,63 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use std::borrow::Cow;
+
+use cloneable_errors::{
+    anyhow, ErrContext, Fingerprinter, IntoErrorIterator, Normalizer, NumberNormalizer,
+    PathNormalizer, UuidNormalizer,
+};
+
+#[test]
+fn test_normalizers() {
+    assert_eq!(NumberNormalizer.normalize("no numbers here"), Cow::Borrowed("no numbers here"));
+    assert_eq!(NumberNormalizer.normalize("port 8080 on 10.0.0.5"), "port # on #.#.#.#");
+
+    assert_eq!(
+        UuidNormalizer.normalize("user 67e55044-10b1-426f-9247-bb680e5fe0c8 not found"),
+        "user <uuid> not found"
+    );
+    // not bounded by a word boundary
+    assert_eq!(
+        UuidNormalizer.normalize("x67e55044-10b1-426f-9247-bb680e5fe0c8"),
+        Cow::Borrowed("x67e55044-10b1-426f-9247-bb680e5fe0c8")
+    );
+
+    assert_eq!(
+        PathNormalizer.normalize("failed to open \"/etc/app.conf\": file not found"),
+        "failed to open \"<path>\": file not found"
+    );
+    assert_eq!(PathNormalizer.normalize("reading C:\\data\\x.txt, ./y and ../z."), "reading <path>, <path> and <path>.");
+    assert_eq!(PathNormalizer.normalize("a/b is not a path"), Cow::Borrowed("a/b is not a path"));
+}
+
+#[test]
+fn test_fingerprints() {
+    let a = anyhow!("request {} failed", 1)
+        .context("handling /api/users/67e55044-10b1-426f-9247-bb680e5fe0c8");
+    let b = anyhow!("request {} failed", 2)
+        .context("handling /api/users/00000000-0000-0000-0000-000000000000");
+    let c = anyhow!("request {} timed out", 2)
+        .context("handling /api/users/00000000-0000-0000-0000-000000000000");
+
+    assert_eq!(a.fingerprint(), b.fingerprint());
+    assert_ne!(a.fingerprint(), c.fingerprint());
+    assert_eq!(a.fingerprint(), a.serializable_copy().fingerprint());
+    assert_eq!(a.fingerprint().as_u64(), b.fingerprint().as_u64());
+
+    // layer boundaries matter
+    let split = anyhow!("ab").context("c");
+    let merged = anyhow!("a").context("bc");
+    assert_ne!(split.fingerprint(), merged.fingerprint());
+
+    // no normalizers
+    let exact = Fingerprinter::new();
+    assert_ne!(a.fingerprint_with(&exact), b.fingerprint_with(&exact));
+    let numbers_only = Fingerprinter::new().with_normalizer(NumberNormalizer);
+    assert_ne!(a.fingerprint_with(&numbers_only), b.fingerprint_with(&numbers_only));
+    assert_eq!(a.fingerprint(), a.fingerprint_with(&Fingerprinter::default()));
+}
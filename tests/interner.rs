@@ -0,0 +1,73 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+#![cfg(feature = "std")]
+
+use std::sync::Arc;
+
+use cloneable_errors::{Interner, SharedString};
+
+#[test]
+fn test_bounded_interner() {
+    let interner = Interner::bounded(2);
+    let a = interner.intern("a");
+    assert!(Arc::ptr_eq(&a, &interner.intern("a")));
+    let b = interner.intern("b");
+    assert_eq!(interner.len(), 2);
+
+    // evicts "a", the oldest string
+    let _c = interner.intern("c");
+    assert_eq!(interner.len(), 2);
+    assert!(!Arc::ptr_eq(&a, &interner.intern("a")));
+    assert!(!Arc::ptr_eq(&b, &interner.intern("b")));
+
+    interner.clear();
+    assert!(interner.is_empty());
+
+    let unbounded = Interner::bounded(0);
+    assert!(!Arc::ptr_eq(&unbounded.intern("a"), &unbounded.intern("a")));
+    assert!(unbounded.is_empty());
+}
+
+#[test]
+fn test_weak_interner() {
+    let interner = Interner::weak();
+    let a = interner.intern("a");
+    assert!(Arc::ptr_eq(&a, &interner.intern("a")));
+    assert_eq!(interner.len(), 1);
+    drop(a);
+    assert!(interner.is_empty());
+
+    // dead entries get cleaned up
+    let kept: Vec<_> = (0..10).map(|i| interner.intern(&format!("kept {i}"))).collect();
+    for i in 0..1000 {
+        drop(interner.intern(&format!("dropped {i}")));
+    }
+    assert_eq!(interner.len(), kept.len());
+
+    let message = format!("connection refused to {}", "10.0.0.5");
+    let a = interner.intern_shared(SharedString::from(message.clone()));
+    assert_eq!(a, interner.intern_shared(SharedString::from(message)));
+    assert!(matches!(interner.intern_shared("static".into()), SharedString::Static("static")));
+}
+
+#[cfg(feature = "bincode")]
+#[test]
+fn test_global_interner() {
+    use bincode::{decode_from_slice, encode_to_vec};
+    use cloneable_errors::{global_interner, set_global_interner};
+
+    assert!(global_interner().is_none());
+    assert!(set_global_interner(Interner::weak()).is_ok());
+    assert!(set_global_interner(Interner::weak()).is_err());
+
+    let config = bincode::config::standard();
+    let buf = encode_to_vec(SharedString::Static("connection refused"), config).unwrap();
+    let a: SharedString = decode_from_slice(&buf, config).unwrap().0;
+    let b: SharedString = decode_from_slice(&buf, config).unwrap().0;
+    assert_eq!(a, b);
+    assert_eq!(global_interner().unwrap().len(), 1);
+}
@@ -0,0 +1,33 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use std::{io, mem::size_of};
+
+use cloneable_errors::{ErrContext, ErrorContext};
+
+#[test]
+fn test_thin_layout() {
+    assert_eq!(size_of::<ErrorContext>(), size_of::<usize>());
+    assert_eq!(size_of::<Result<(), ErrorContext>>(), size_of::<usize>());
+    assert_eq!(size_of::<Option<ErrorContext>>(), size_of::<usize>());
+}
+
+#[test]
+fn test_accessors() {
+    let error = io::Error::other("disk on fire").context("writing the file");
+    assert_eq!(error.message().as_str(), "writing the file");
+    assert_eq!(error.cause().unwrap().to_string(), "disk on fire");
+    assert_eq!(error.cause_type_name(), Some(std::any::type_name::<io::Error>()));
+
+    let copy = error.clone();
+    assert!(std::ptr::eq(error.message(), copy.message()));
+    assert_eq!(error, copy);
+
+    let root = ErrorContext::new("root");
+    assert!(root.cause().is_none());
+    assert!(root.cause_type_name().is_none());
+}
@@ -0,0 +1,77 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use std::{error::Error, io, sync::Arc};
+
+use cloneable_errors::{ChainLink, ErrContext, ErrorContext, IntoErrorIterator, LinkKind};
+
+fn sample() -> ErrorContext {
+    io::Error::other("disk on fire")
+        .context("writing the file")
+        .serializable_copy()
+        .context("saving the document")
+}
+
+#[test]
+fn test_links() {
+    let error = sample();
+    let links: Vec<_> = error.links().collect();
+    assert_eq!(links.len(), 3);
+
+    let kinds: Vec<_> = links.iter().map(ChainLink::kind).collect();
+    assert_eq!(kinds, [LinkKind::Context, LinkKind::Serializable, LinkKind::Serializable]);
+    assert!(links.iter().enumerate().all(|(i, link)| link.depth() == i));
+    assert_eq!(links[0].type_name(), Some(std::any::type_name::<ErrorContext>()));
+    assert_eq!(links[2].error().to_string(), "disk on fire");
+
+    let error = io::Error::other("disk on fire").context("writing the file");
+    let links: Vec<_> = error.links().collect();
+    assert_eq!(links[1].kind(), LinkKind::Foreign);
+    assert_eq!(links[1].type_name(), Some(std::any::type_name::<io::Error>()));
+    assert!(links[1].error().downcast_ref::<io::Error>().is_some());
+
+    // the name of a wrapper type is not shown for the error it wraps
+    let wrapped: Arc<dyn Error + Send + Sync> = Arc::new(io::Error::other("disk on fire"));
+    let error = wrapped.context("writing the file");
+    let links: Vec<_> = error.links().collect();
+    assert!(links[1].error().downcast_ref::<io::Error>().is_some());
+    assert_eq!(links[1].type_name(), None);
+}
+
+#[test]
+fn test_reverse_links() {
+    let error = sample();
+    let messages: Vec<_> = error.links().rev().map(|link| link.error().to_string()).collect();
+    assert_eq!(messages, ["disk on fire", "writing the file", "saving the document"]);
+
+    let mut links = error.links();
+    assert_eq!(links.len(), 3);
+    assert_eq!(links.nth_from_root(1).unwrap().depth(), 1);
+    assert_eq!(links.len(), 1);
+    assert_eq!(links.next().unwrap().depth(), 0);
+    assert!(links.truncation().is_none());
+}
+
+#[cfg(feature = "extensions")]
+#[test]
+fn test_link_extensions() {
+    use cloneable_errors::Extension;
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct A(u32);
+    impl Extension for A {}
+
+    let error = ErrorContext::new("inner")
+        .with_extension(Arc::new(A(1)))
+        .context("outer")
+        .without_extension::<A>();
+    let links: Vec<_> = error.links().collect();
+    assert!(!links[0].extensions().contains::<A>());
+    assert!(links[0].extensions().masks::<A>());
+    assert_eq!(links[1].extensions().get::<A>().as_deref(), Some(&A(1)));
+    assert!(!links[1].extensions().masks::<A>());
+}
@@ -0,0 +1,157 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+#![cfg(feature = "std")]
+
+use std::{cell::RefCell, future::ready, io, time::Duration};
+
+use futures::executor::block_on;
+
+use cloneable_errors::{anyhow, ErrContext, ErrorClass, IntoErrorIterator, RetryPolicy};
+
+#[test]
+fn test_io_classification() {
+    let timeout = io::Error::from(io::ErrorKind::TimedOut).context("fetching data");
+    let missing = io::Error::from(io::ErrorKind::NotFound).context("opening file");
+    let other = io::Error::other("huh").context("doing stuff");
+
+    assert_eq!(timeout.classify(), Some(ErrorClass::Retryable));
+    assert_eq!(missing.clone().context("loading config").classify(), Some(ErrorClass::Permanent));
+    assert_eq!(other.classify(), None);
+    assert_eq!(anyhow!("no io errors here").classify(), None);
+}
+
+#[test]
+fn test_retry_backoff() {
+    let policy = RetryPolicy::new(4)
+        .with_delay(Duration::from_millis(100))
+        .with_max_delay(Duration::from_millis(300));
+    let delays = RefCell::new(Vec::new());
+
+    let error = block_on(policy.retry_async(
+        |delay| {
+            delays.borrow_mut().push(delay);
+            ready(())
+        },
+        |attempt| ready(Err::<(), _>(io::Error::from(io::ErrorKind::TimedOut).context(format!("attempt {attempt}")))),
+    ))
+    .unwrap_err();
+
+    assert_eq!(format!("{error}"), "operation failed after 4 attempts");
+    assert_eq!(format!("{}", error.error_chain().nth(1).unwrap()), "attempt 4");
+    assert_eq!(
+        *delays.borrow(),
+        [Duration::from_millis(100), Duration::from_millis(200), Duration::from_millis(300)]
+    );
+
+    // permanent errors are not retried
+    let error = policy
+        .retry(|_| Err::<(), _>(io::Error::from(io::ErrorKind::NotFound).context("missing")))
+        .unwrap_err();
+    assert_eq!(format!("{error}"), "missing");
+}
+
+#[cfg(feature = "extensions")]
+mod extensions {
+    use std::{cell::RefCell, future::ready, io, sync::Arc, time::Duration};
+
+    use futures::executor::block_on;
+
+    use cloneable_errors::{
+        anyhow, ErrContext, ErrorClass, ErrorContext, IntoErrorIterator, ResExtensions, RetryAfter,
+        RetryHistory, RetryPolicy,
+    };
+
+    fn retryable(msg: &'static str) -> ErrorContext {
+        anyhow!(msg, extend: Arc::new(ErrorClass::Retryable))
+    }
+
+    #[test]
+    fn test_extension_classification() {
+        let timeout = io::Error::from(io::ErrorKind::TimedOut).context("fetching data");
+        let permanent = timeout.clone().with_extension(Arc::new(ErrorClass::Permanent));
+
+        assert_eq!(permanent.classify(), Some(ErrorClass::Permanent));
+        assert_eq!(permanent.context("outer").classify(), Some(ErrorClass::Permanent));
+        assert_eq!(
+            timeout.without_extension::<ErrorClass>().classify(),
+            Some(ErrorClass::Retryable)
+        );
+    }
+
+    #[test]
+    fn test_retry_until_success() {
+        let policy = RetryPolicy::new(5).with_delay(Duration::from_millis(1));
+        let result = policy.retry(|attempt| if attempt < 3 { Err(retryable("not yet")) } else { Ok(attempt) });
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[test]
+    fn test_retry_stops_on_permanent_errors() {
+        let policy = RetryPolicy::new(5).with_delay(Duration::from_millis(1));
+        let mut attempts = 0;
+        let error = policy
+            .retry(|attempt| {
+                attempts = attempt;
+                if attempt == 1 {
+                    Err(retryable("flaky"))
+                } else {
+                    Err::<(), _>(io::Error::from(io::ErrorKind::PermissionDenied).context("denied"))
+                }
+            })
+            .unwrap_err();
+
+        assert_eq!(attempts, 2);
+        assert_eq!(format!("{error}"), "operation failed after 2 attempts");
+        let history = error.find_extension::<RetryHistory>().unwrap();
+        assert_eq!(history.attempts, 2);
+        assert_eq!(format!("{}", history.errors[0]), "flaky");
+        assert_eq!(format!("{}", history.errors[1]), "denied");
+
+        // a single failed attempt is returned as is
+        let error = policy.retry(|_| Err::<(), _>(anyhow!("unclassified"))).unwrap_err();
+        assert_eq!(format!("{error:?}"), "unclassified");
+        assert!(error.find_extension::<RetryHistory>().is_none());
+
+        let policy = policy.retry_unclassified(true);
+        let error = policy.retry(|_| Err::<(), _>(anyhow!("unclassified"))).unwrap_err();
+        assert_eq!(error.find_extension::<RetryHistory>().unwrap().attempts, 5);
+    }
+
+    #[test]
+    fn test_retry_async_delays() {
+        let policy = RetryPolicy::new(5)
+            .with_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_secs(1));
+        let delays = RefCell::new(Vec::new());
+
+        let result = block_on(policy.retry_async(
+            |delay| {
+                delays.borrow_mut().push(delay);
+                ready(())
+            },
+            |attempt| {
+                ready(match attempt {
+                    3 => Err(retryable("slow down")).extend(Arc::new(RetryAfter(Duration::from_secs(5)))),
+                    1..5 => Err(retryable("try again")),
+                    _ => Ok(attempt),
+                })
+            },
+        ));
+
+        assert_eq!(result.unwrap(), 5);
+        assert_eq!(
+            *delays.borrow(),
+            [
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                // capped RetryAfter hint
+                Duration::from_secs(1),
+                Duration::from_millis(800),
+            ]
+        );
+    }
+}
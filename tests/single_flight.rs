@@ -0,0 +1,109 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+#![cfg(feature = "futures")]
+
+use std::{
+    future::Ready,
+    panic::{catch_unwind, AssertUnwindSafe},
+    pin::pin,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use futures::{channel::oneshot, executor::block_on, join, poll};
+
+use cloneable_errors::{anyhow, ErrorContext, SingleFlight};
+
+#[test]
+fn test_shared_computation() {
+    let flights = SingleFlight::<&str, u32>::new();
+    let calls = AtomicU32::new(0);
+    let (tx, rx) = oneshot::channel::<()>();
+
+    // short messages are compared by value, so number the computations to tell them apart
+    let compute = || {
+        let call = calls.fetch_add(1, Ordering::Relaxed) + 1;
+        async move { Err(anyhow!("failed on call {}", call)) }
+    };
+    let wait_then_fail = || {
+        let call = calls.fetch_add(1, Ordering::Relaxed) + 1;
+        async move {
+            rx.await.unwrap();
+            Err(anyhow!("failed on call {}", call))
+        }
+    };
+
+    let (a, b, c, ()) = block_on(async {
+        join!(
+            flights.run("key", wait_then_fail),
+            flights.run("key", compute),
+            flights.run("key", compute),
+            async { tx.send(()).unwrap() },
+        )
+    });
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+    let a = a.unwrap_err();
+    assert_eq!(a, b.unwrap_err());
+    assert_eq!(a, c.unwrap_err());
+    assert!(flights.is_empty());
+
+    // the next call starts a new computation
+    let d = block_on(flights.run("key", compute)).unwrap_err();
+    assert_eq!(calls.load(Ordering::Relaxed), 2);
+    assert_ne!(a, d);
+}
+
+#[test]
+fn test_leader_dropped() {
+    let flights = SingleFlight::<u32, u32>::new();
+    let (tx, rx) = oneshot::channel::<u32>();
+
+    block_on(async {
+        let mut leader = Box::pin(flights.run(1, || async { Ok(rx.await.unwrap()) }));
+        assert!(poll!(&mut leader).is_pending());
+        let mut follower = pin!(flights.run(1, || async { unreachable!("the computation is shared") }));
+        assert!(poll!(&mut follower).is_pending());
+
+        // the follower keeps driving the computation
+        drop(leader);
+        assert_eq!(flights.len(), 1);
+        tx.send(5).unwrap();
+        assert_eq!(follower.await.unwrap(), 5);
+    });
+    assert!(flights.is_empty());
+}
+
+#[test]
+fn test_all_callers_dropped() {
+    let flights = SingleFlight::<u32, u32>::new();
+    let (tx, rx) = oneshot::channel::<u32>();
+
+    block_on(async {
+        let mut leader = Box::pin(flights.run(1, || async { Ok(rx.await.unwrap()) }));
+        assert!(poll!(&mut leader).is_pending());
+        assert_eq!(flights.len(), 1);
+        drop(leader);
+    });
+    // the computation was cancelled
+    assert!(flights.is_empty());
+    assert!(tx.is_canceled());
+
+    assert_eq!(block_on(flights.run(1, || async { Ok(7) })).unwrap(), 7);
+}
+
+#[test]
+fn test_panicking_function() {
+    let flights = SingleFlight::<u32, u32>::new();
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        block_on(flights.run(1, || -> Ready<Result<u32, ErrorContext>> { panic!("no future for you") }))
+    }));
+    assert!(result.is_err());
+
+    // the panic didn't poison the SingleFlight
+    assert!(flights.is_empty());
+    assert_eq!(block_on(flights.run(1, || async { Ok(3) })).unwrap(), 3);
+}
@@ -0,0 +1,49 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use std::{borrow::Cow, mem::size_of};
+
+use cloneable_errors::{anyhow, shared_format, ByContent, ErrorContext, InlineStr, SharedString};
+
+#[test]
+fn test_static_shared_string_equality() {
+    let string = SharedString::Static("helo");
+    assert_eq!(string, string.clone());
+    assert_ne!(string, SharedString::Static("hel"));
+
+    // errors with static messages are equal to their clones
+    let error = ErrorContext::new("static message");
+    assert_eq!(error, error.clone());
+}
+
+#[test]
+fn test_inline_shared_strings() {
+    assert!(size_of::<SharedString>() <= 24);
+
+    let short = SharedString::from(String::from("helo"));
+    assert!(matches!(short, SharedString::Inline(_)));
+    // inline strings are compared by value, regardless of where they came from
+    assert_eq!(short, short.clone());
+    assert_eq!(short, SharedString::from(Box::<str>::from("helo")));
+    assert_ne!(short, SharedString::from(String::from("hel")));
+    assert_eq!(ByContent(short.clone()), ByContent(SharedString::Static("helo")));
+    assert_eq!(&*short, "helo");
+    assert_eq!(short.as_ref(), "helo");
+
+    let long = "x".repeat(InlineStr::CAPACITY + 1);
+    assert!(matches!(SharedString::from(long.clone()), SharedString::Arc(_)));
+    assert!(matches!(SharedString::from(long.into_boxed_str()), SharedString::Arc(_)));
+    assert!(InlineStr::new(&"x".repeat(InlineStr::CAPACITY)).is_some());
+
+    assert!(matches!(SharedString::from(Cow::Borrowed("helo")), SharedString::Static("helo")));
+    assert!(matches!(SharedString::from(Cow::<str>::Owned("helo".into())), SharedString::Inline(_)));
+
+    assert!(matches!(shared_format!("helo"), SharedString::Static("helo")));
+    let x = 5;
+    assert_eq!(shared_format!("x = {x}").as_str(), "x = 5");
+    assert!(matches!(anyhow!("x = {}", x).message(), SharedString::Inline(_)));
+}
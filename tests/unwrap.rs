@@ -0,0 +1,124 @@
+/*
+* This file is part of the cloneable_errors library, licensed under the MIT license:
+* https://github.com/mini-bomba/cloneable_errors
+*
+* Copyright (C) 2025 mini_bomba
+*/
+
+use std::{error::Error, fmt::Display, sync::Arc};
+
+use cloneable_errors::{anyhow, ErrContext, ErrorContext, IntoErrorIterator, SerializableError};
+
+#[derive(Debug)]
+#[cfg_attr(not(feature = "std"), allow(dead_code))] // only used with custom unwrappers
+struct Inner;
+
+impl Display for Inner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "inner")
+    }
+}
+
+impl Error for Inner {}
+
+/// A wrapper that doesn't implement Deref
+#[derive(Debug)]
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+struct Opaque(Arc<Inner>);
+
+impl Display for Opaque {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Error for Opaque {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.0.source()
+    }
+}
+
+#[test]
+fn test_builtin_unwrapping() {
+    let shared = Arc::new(anyhow!("shared"));
+    let error = shared.clone().context("outer");
+    assert!(error.error_chain().nth(1).unwrap().is::<ErrorContext>());
+
+    let boxed = Box::new(anyhow!("boxed").serializable_copy()).context("outer");
+    assert!(boxed.error_chain().nth(1).unwrap().is::<SerializableError>());
+
+    let dyn_arc: Arc<dyn Error + Send + Sync> = Arc::new(anyhow!("dyn"));
+    let error = dyn_arc.context("outer");
+    assert!(error.error_chain().nth(1).unwrap().is::<ErrorContext>());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_custom_unwrapper() {
+    use cloneable_errors::register_unwrapper;
+
+    let error = Opaque(Arc::new(Inner)).context("outer");
+    assert!(error.error_chain().nth(1).unwrap().is::<Opaque>());
+
+    register_unwrapper(|err| err.downcast_ref::<Opaque>().map(|o| &*o.0 as &(dyn Error + 'static)));
+    assert!(error.error_chain().nth(1).unwrap().is::<Inner>());
+    assert_eq!(error.error_chain().count(), 2);
+}
+
+#[cfg(feature = "extensions")]
+#[test]
+fn test_extensions_through_arc() {
+    use cloneable_errors::Extension;
+
+    struct Tag;
+    impl Extension for Tag {}
+
+    let tagged = Arc::new(anyhow!("tagged", extend: Arc::new(Tag)));
+    assert!(tagged.context("outer").find_extension::<Tag>().is_some());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_misbehaving_unwrappers() {
+    use cloneable_errors::register_unwrapper;
+
+    #[derive(Debug)]
+    struct Selfish;
+    impl Display for Selfish {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "selfish")
+        }
+    }
+    impl Error for Selfish {}
+
+    #[derive(Debug)]
+    struct Ping;
+    impl Display for Ping {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "ping")
+        }
+    }
+    impl Error for Ping {}
+
+    #[derive(Debug)]
+    struct Pong;
+    impl Display for Pong {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "pong")
+        }
+    }
+    impl Error for Pong {}
+
+    // returns the error it was given
+    register_unwrapper(|err| err.is::<Selfish>().then_some(err));
+    // two unwrappers pointing at each other
+    register_unwrapper(|err| err.is::<Ping>().then_some(&Pong as &(dyn Error + 'static)));
+    register_unwrapper(|err| err.is::<Pong>().then_some(&Ping as &(dyn Error + 'static)));
+
+    let error = Selfish.context("outer");
+    assert!(error.error_chain().nth(1).unwrap().is::<Selfish>());
+    assert_eq!(error.error_chain().count(), 2);
+
+    let error = Ping.context("outer");
+    assert_eq!(error.error_chain().count(), 2);
+}